@@ -1,10 +1,28 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
 use inquire::ui::{Attributes, Color, RenderConfig, StyleSheet};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ActionColors;
+
+/// Per-action color overrides from the config file, installed once at startup.
+static COLORS: OnceLock<BTreeMap<Action, ActionColors>> = OnceLock::new();
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub fn set_colors(colors: BTreeMap<Action, ActionColors>) {
+    let _ = COLORS.set(colors);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Action {
     Create,
     CreateWithDir,
+    FromTemplate,
     Attach,
+    Resurrect,
+    Rename,
+    Kill,
     Delete,
 }
 
@@ -13,25 +31,45 @@ impl std::fmt::Display for Action {
         match self {
             Action::Create => write!(f, "Create new session"),
             Action::CreateWithDir => write!(f, "Create new session with directory"),
+            Action::FromTemplate => write!(f, "Create session from template"),
             Action::Attach => write!(f, "Attach to session"),
+            Action::Resurrect => write!(f, "Resurrect exited session"),
+            Action::Rename => write!(f, "Rename session"),
+            Action::Kill => write!(f, "Kill running session"),
             Action::Delete => write!(f, "Delete session"),
         }
     }
 }
 
 impl Action {
+    fn custom_colors(&self) -> Option<&'static ActionColors> {
+        COLORS.get()?.get(self)
+    }
+
     fn color(&self) -> Color {
+        if let Some(color) = self.custom_colors().and_then(|c| c.prompt) {
+            return color.0;
+        }
         match self {
-            Action::Create | Action::CreateWithDir => Color::LightGreen,
+            Action::Create | Action::CreateWithDir | Action::FromTemplate => Color::LightGreen,
             Action::Attach => Color::LightCyan,
+            Action::Resurrect => Color::LightYellow,
+            Action::Rename => Color::LightBlue,
+            Action::Kill => Color::LightMagenta,
             Action::Delete => Color::LightRed,
         }
     }
 
     fn highlight_color(&self) -> Color {
+        if let Some(color) = self.custom_colors().and_then(|c| c.highlight) {
+            return color.0;
+        }
         match self {
-            Action::Create | Action::CreateWithDir => Color::DarkGreen,
+            Action::Create | Action::CreateWithDir | Action::FromTemplate => Color::DarkGreen,
             Action::Attach => Color::DarkCyan,
+            Action::Resurrect => Color::DarkYellow,
+            Action::Rename => Color::DarkBlue,
+            Action::Kill => Color::DarkMagenta,
             Action::Delete => Color::DarkRed,
         }
     }
@@ -68,11 +106,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn action_display_from_template() {
+        assert_eq!(
+            Action::FromTemplate.to_string(),
+            "Create session from template"
+        );
+    }
+
     #[test]
     fn action_display_attach() {
         assert_eq!(Action::Attach.to_string(), "Attach to session");
     }
 
+    #[test]
+    fn action_display_resurrect() {
+        assert_eq!(Action::Resurrect.to_string(), "Resurrect exited session");
+    }
+
+    #[test]
+    fn action_display_rename() {
+        assert_eq!(Action::Rename.to_string(), "Rename session");
+    }
+
+    #[test]
+    fn action_display_kill() {
+        assert_eq!(Action::Kill.to_string(), "Kill running session");
+    }
+
     #[test]
     fn action_display_delete() {
         assert_eq!(Action::Delete.to_string(), "Delete session");
@@ -88,11 +149,31 @@ mod tests {
         assert_eq!(Action::CreateWithDir.color(), Color::LightGreen);
     }
 
+    #[test]
+    fn action_color_returns_light_green_for_from_template() {
+        assert_eq!(Action::FromTemplate.color(), Color::LightGreen);
+    }
+
     #[test]
     fn action_color_returns_light_cyan_for_attach() {
         assert_eq!(Action::Attach.color(), Color::LightCyan);
     }
 
+    #[test]
+    fn action_color_returns_light_yellow_for_resurrect() {
+        assert_eq!(Action::Resurrect.color(), Color::LightYellow);
+    }
+
+    #[test]
+    fn action_color_returns_light_blue_for_rename() {
+        assert_eq!(Action::Rename.color(), Color::LightBlue);
+    }
+
+    #[test]
+    fn action_color_returns_light_magenta_for_kill() {
+        assert_eq!(Action::Kill.color(), Color::LightMagenta);
+    }
+
     #[test]
     fn action_color_returns_light_red_for_delete() {
         assert_eq!(Action::Delete.color(), Color::LightRed);
@@ -108,11 +189,31 @@ mod tests {
         assert_eq!(Action::CreateWithDir.highlight_color(), Color::DarkGreen);
     }
 
+    #[test]
+    fn action_highlight_color_returns_dark_green_for_from_template() {
+        assert_eq!(Action::FromTemplate.highlight_color(), Color::DarkGreen);
+    }
+
     #[test]
     fn action_highlight_color_returns_dark_cyan_for_attach() {
         assert_eq!(Action::Attach.highlight_color(), Color::DarkCyan);
     }
 
+    #[test]
+    fn action_highlight_color_returns_dark_yellow_for_resurrect() {
+        assert_eq!(Action::Resurrect.highlight_color(), Color::DarkYellow);
+    }
+
+    #[test]
+    fn action_highlight_color_returns_dark_blue_for_rename() {
+        assert_eq!(Action::Rename.highlight_color(), Color::DarkBlue);
+    }
+
+    #[test]
+    fn action_highlight_color_returns_dark_magenta_for_kill() {
+        assert_eq!(Action::Kill.highlight_color(), Color::DarkMagenta);
+    }
+
     #[test]
     fn action_highlight_color_returns_dark_red_for_delete() {
         assert_eq!(Action::Delete.highlight_color(), Color::DarkRed);
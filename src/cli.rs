@@ -0,0 +1,314 @@
+use std::path::PathBuf;
+
+use clap::{Parser, Subcommand};
+
+use crate::output::Format;
+use crate::sort::SortOrder;
+
+#[derive(Parser)]
+#[command(version)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Number of candidates to display at once [default: 24]
+    #[arg(long, value_parser = parse_page_size)]
+    pub page_size: Option<usize>,
+
+    /// Session order in pickers [default: recent]
+    #[arg(long, value_enum)]
+    pub sort: Option<SortOrder>,
+
+    /// Rename Guake tab to session name on create/attach/rename
+    #[arg(long, overrides_with = "no_guake")]
+    pub guake: bool,
+
+    /// Do not rename the Guake tab, even when the config file enables it
+    #[arg(long, overrides_with = "guake")]
+    pub no_guake: bool,
+
+    /// Attach instead of creating when a running session was already created
+    /// in the chosen directory
+    #[arg(long, overrides_with = "no_reuse")]
+    pub reuse: bool,
+
+    /// Always create, even when the config file enables `reuse`
+    #[arg(long, overrides_with = "reuse")]
+    pub no_reuse: bool,
+
+    /// Always complete hidden directories in the directory prompt
+    #[arg(long, overrides_with = "no_hidden")]
+    pub hidden: bool,
+
+    /// Only complete hidden directories once a `.` is typed
+    #[arg(long, overrides_with = "hidden")]
+    pub no_hidden: bool,
+
+    /// Print banner and exit
+    #[arg(long, conflicts_with = "no_banner")]
+    pub banner: bool,
+
+    /// Suppress banner display
+    #[arg(long, conflicts_with = "banner", overrides_with = "show_banner")]
+    pub no_banner: bool,
+
+    /// Show the banner, even when the config file sets `no_banner`
+    #[arg(long, overrides_with = "no_banner")]
+    pub show_banner: bool,
+}
+
+/// Rejects a page size of 0, like the config file does.
+fn parse_page_size(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) => Err("must be greater than 0".to_string()),
+        Ok(size) => Ok(size),
+        Err(err) => Err(format!("{err}")),
+    }
+}
+
+/// Non-interactive commands. Running `zism` without one starts the
+/// interactive prompts.
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// List sessions
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t)]
+        format: Format,
+    },
+
+    /// Attach to a session
+    Attach {
+        /// Session name
+        name: String,
+    },
+
+    /// Create a new session
+    New {
+        /// Session name
+        name: String,
+
+        /// Working directory for the new session
+        #[arg(long)]
+        dir: Option<PathBuf>,
+
+        /// Layout name or path to a layout file
+        #[arg(long)]
+        layout: Option<String>,
+
+        /// Use the directory's checked-in `.zellij/layout.kdl` or `zism.kdl`
+        #[arg(long)]
+        project_layout: bool,
+
+        /// Load variables from the configured env file in the directory
+        #[arg(long)]
+        env_file: bool,
+    },
+
+    /// Show what zism recorded about a session
+    Info {
+        /// Session name
+        name: String,
+    },
+
+    /// Add or remove tags on a session
+    Tag {
+        /// Session name
+        name: String,
+
+        /// Tags to add (or remove with --remove)
+        #[arg(required = true)]
+        tags: Vec<String>,
+
+        /// Remove the tags instead of adding them
+        #[arg(long)]
+        remove: bool,
+    },
+
+    /// Kill (stop) running sessions, keeping them resurrectable
+    Kill {
+        /// Session names
+        #[arg(required = true)]
+        names: Vec<String>,
+    },
+
+    /// Delete one or more exited sessions
+    Delete {
+        /// Session names
+        #[arg(required = true)]
+        names: Vec<String>,
+
+        /// Also kill and delete sessions that are still running
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Compact session switcher for a Zellij floating pane
+    Popup,
+
+    /// Inspect the configuration file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug, PartialEq, Eq)]
+pub enum ConfigCommand {
+    /// Print the effective configuration (file merged with flags)
+    Show,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Option<Command> {
+        Args::try_parse_from(args).unwrap().command
+    }
+
+    #[test]
+    fn no_subcommand_is_interactive() {
+        assert_eq!(parse(&["zism", "--guake"]), None);
+    }
+
+    #[test]
+    fn parses_list_format() {
+        assert_eq!(
+            parse(&["zism", "list", "--format", "json"]),
+            Some(Command::List {
+                format: Format::Json
+            })
+        );
+        assert_eq!(
+            parse(&["zism", "list"]),
+            Some(Command::List {
+                format: Format::Plain
+            })
+        );
+    }
+
+    #[test]
+    fn parses_attach() {
+        assert_eq!(
+            parse(&["zism", "attach", "api"]),
+            Some(Command::Attach {
+                name: "api".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn parses_new_with_dir() {
+        assert_eq!(
+            parse(&["zism", "new", "api", "--dir", "/srv/api"]),
+            Some(Command::New {
+                name: "api".to_string(),
+                dir: Some(PathBuf::from("/srv/api")),
+                layout: None,
+                project_layout: false,
+                env_file: false,
+            })
+        );
+        assert!(matches!(
+            parse(&[
+                "zism",
+                "new",
+                "api",
+                "--dir",
+                ".",
+                "--project-layout",
+                "--env-file"
+            ]),
+            Some(Command::New {
+                project_layout: true,
+                env_file: true,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parses_delete_with_multiple_names() {
+        assert_eq!(
+            parse(&["zism", "delete", "a", "b"]),
+            Some(Command::Delete {
+                names: vec!["a".to_string(), "b".to_string()],
+                force: false,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_delete_force() {
+        assert_eq!(
+            parse(&["zism", "delete", "-f", "a"]),
+            Some(Command::Delete {
+                names: vec!["a".to_string()],
+                force: true,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_tag_remove() {
+        assert_eq!(
+            parse(&["zism", "tag", "api", "work", "--remove"]),
+            Some(Command::Tag {
+                name: "api".to_string(),
+                tags: vec!["work".to_string()],
+                remove: true,
+            })
+        );
+    }
+
+    #[test]
+    fn parses_kill() {
+        assert_eq!(
+            parse(&["zism", "kill", "a"]),
+            Some(Command::Kill {
+                names: vec!["a".to_string()]
+            })
+        );
+    }
+
+    #[test]
+    fn parses_config_show() {
+        assert_eq!(
+            parse(&["zism", "config", "show"]),
+            Some(Command::Config {
+                command: ConfigCommand::Show
+            })
+        );
+    }
+
+    #[test]
+    fn parses_popup() {
+        assert_eq!(parse(&["zism", "popup"]), Some(Command::Popup));
+    }
+
+    #[test]
+    fn last_of_a_flag_and_its_negation_wins() {
+        let args = Args::try_parse_from(["zism", "--guake", "--no-guake"]).unwrap();
+        assert!(!args.guake);
+        assert!(args.no_guake);
+        let args = Args::try_parse_from(["zism", "--no-reuse", "--reuse"]).unwrap();
+        assert!(args.reuse);
+        assert!(!args.no_reuse);
+        let args = Args::try_parse_from(["zism", "--no-banner", "--show-banner"]).unwrap();
+        assert!(args.show_banner);
+        assert!(!args.no_banner);
+    }
+
+    #[test]
+    fn page_size_must_be_positive() {
+        assert!(Args::try_parse_from(["zism", "--page-size", "0"]).is_err());
+        let args = Args::try_parse_from(["zism", "--page-size", "1"]).unwrap();
+        assert_eq!(args.page_size, Some(1));
+    }
+
+    #[test]
+    fn delete_requires_a_name() {
+        assert!(Args::try_parse_from(["zism", "delete"]).is_err());
+    }
+}
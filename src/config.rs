@@ -0,0 +1,519 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use inquire::ui::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::action::Action;
+use crate::cli::Args;
+use crate::naming::NamingStrategy;
+use crate::paths::{expand_tilde, expand_vars};
+use crate::sort::SortOrder;
+use crate::template::Template;
+use crate::xdg;
+
+pub const DEFAULT_PAGE_SIZE: usize = 24;
+pub const DEFAULT_PROJECT_DEPTH: usize = 3;
+
+/// When zism asks for confirmation before acting on a session.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfirmPolicy {
+    /// Confirm before every action that changes a session
+    Always,
+    /// Confirm only before killing or deleting sessions
+    #[default]
+    Destructive,
+    /// Never ask
+    Never,
+}
+
+impl ConfirmPolicy {
+    pub fn applies_to(self, action: Action) -> bool {
+        match self {
+            ConfirmPolicy::Always => true,
+            ConfirmPolicy::Destructive => matches!(action, Action::Kill | Action::Delete),
+            ConfirmPolicy::Never => false,
+        }
+    }
+}
+
+/// A color written as a name (`light_green`), `#rrggbb` or an ANSI value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorName(pub Color);
+
+const COLOR_NAMES: &[(&str, Color)] = &[
+    ("black", Color::Black),
+    ("light_red", Color::LightRed),
+    ("dark_red", Color::DarkRed),
+    ("light_green", Color::LightGreen),
+    ("dark_green", Color::DarkGreen),
+    ("light_yellow", Color::LightYellow),
+    ("dark_yellow", Color::DarkYellow),
+    ("light_blue", Color::LightBlue),
+    ("dark_blue", Color::DarkBlue),
+    ("light_magenta", Color::LightMagenta),
+    ("dark_magenta", Color::DarkMagenta),
+    ("light_cyan", Color::LightCyan),
+    ("dark_cyan", Color::DarkCyan),
+    ("white", Color::White),
+    ("grey", Color::Grey),
+    ("dark_grey", Color::DarkGrey),
+];
+
+impl TryFrom<String> for ColorName {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if let Some((_, color)) = COLOR_NAMES.iter().find(|(name, _)| *name == value) {
+            return Ok(Self(*color));
+        }
+        if let Some(hex) = value.strip_prefix('#')
+            && hex.len() == 6
+            && let Ok(rgb) = u32::from_str_radix(hex, 16)
+        {
+            let [_, r, g, b] = rgb.to_be_bytes();
+            return Ok(Self(Color::rgb(r, g, b)));
+        }
+        if let Ok(ansi) = value.parse::<u8>() {
+            return Ok(Self(Color::AnsiValue(ansi)));
+        }
+        Err(format!("unknown color '{value}'"))
+    }
+}
+
+impl From<ColorName> for String {
+    fn from(value: ColorName) -> Self {
+        match value.0 {
+            Color::Rgb { r, g, b } => format!("#{r:02x}{g:02x}{b:02x}"),
+            Color::AnsiValue(ansi) => ansi.to_string(),
+            color => COLOR_NAMES
+                .iter()
+                .find(|(_, c)| *c == color)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl Serialize for ColorName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&String::from(*self))
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::try_from(value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActionColors {
+    /// Prompt text color
+    pub prompt: Option<ColorName>,
+    /// Background of the highlighted option
+    pub highlight: Option<ColorName>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub page_size: usize,
+    /// Session order in pickers
+    pub sort: SortOrder,
+    pub guake: bool,
+    pub no_banner: bool,
+    /// Attach to the session already created in a directory instead of
+    /// asking whether to create another one
+    pub reuse: bool,
+    /// Complete hidden directories even when the typed name has no leading `.`
+    pub show_hidden: bool,
+    /// Action preselected in the action picker
+    pub default_action: Option<Action>,
+    /// Directory that relative paths in the directory prompt start from
+    pub base_dir: Option<PathBuf>,
+    /// Directories searched for git repositories to offer as projects
+    pub project_roots: Vec<PathBuf>,
+    /// How many levels below each project root to search
+    pub project_depth: usize,
+    /// How session names are derived from directories
+    pub naming: NamingStrategy,
+    /// Layout preselected in the layout picker
+    pub default_layout: Option<String>,
+    /// Layouts used without asking for directories under the given paths
+    pub directory_layouts: BTreeMap<PathBuf, String>,
+    /// Environment variables for sessions created under the given paths
+    pub directory_env: BTreeMap<PathBuf, BTreeMap<String, String>>,
+    /// File in the session directory to read variables from, such as `.env`.
+    /// Off (empty) by default, since such a file can set `PATH` or `LD_PRELOAD`
+    pub env_file: String,
+    pub confirm: ConfirmPolicy,
+    pub colors: BTreeMap<Action, ActionColors>,
+    /// Named session templates offered by the template action
+    pub templates: BTreeMap<String, Template>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            sort: SortOrder::default(),
+            guake: false,
+            no_banner: false,
+            reuse: false,
+            show_hidden: false,
+            default_action: None,
+            base_dir: None,
+            project_roots: Vec::new(),
+            project_depth: DEFAULT_PROJECT_DEPTH,
+            naming: NamingStrategy::default(),
+            default_layout: None,
+            directory_layouts: BTreeMap::new(),
+            directory_env: BTreeMap::new(),
+            env_file: String::new(),
+            confirm: ConfirmPolicy::default(),
+            colors: BTreeMap::new(),
+            templates: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    xdg::config_home().join("zism").join("config.toml")
+}
+
+/// A per-directory table key in the canonical form the session directory is
+/// compared in, so keys through symlinks still match. Directories that do
+/// not exist yet keep their expanded form.
+fn directory_key(dir: &Path) -> PathBuf {
+    let dir = expand_tilde(dir);
+    dir.canonicalize().unwrap_or(dir)
+}
+
+/// Expands `~` and `$VAR` in configured environment variable values, so
+/// paths such as `KUBECONFIG` need not be spelled out per machine.
+fn expand_env_values(vars: &mut BTreeMap<String, String>) {
+    for value in vars.values_mut() {
+        let expanded = expand_tilde(Path::new(&expand_vars(value)));
+        *value = expanded.to_string_lossy().into_owned();
+    }
+}
+
+/// Applies a command-line flag and its `--no-` negation to a config value.
+/// clap keeps only the last of the two, so at most one is set.
+fn set_flag(value: &mut bool, on: bool, off: bool) {
+    if on {
+        *value = true;
+    } else if off {
+        *value = false;
+    }
+}
+
+impl Config {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut config: Config = toml::from_str(input)?;
+        if config.page_size == 0 {
+            bail!("page_size must be greater than 0");
+        }
+        if config.project_depth == 0 {
+            bail!("project_depth must be greater than 0");
+        }
+        config.base_dir = config.base_dir.as_deref().map(expand_tilde);
+        config.project_roots = config
+            .project_roots
+            .iter()
+            .map(|root| expand_tilde(root))
+            .collect();
+        config.directory_layouts = std::mem::take(&mut config.directory_layouts)
+            .into_iter()
+            .map(|(dir, layout)| (directory_key(&dir), layout))
+            .collect();
+        config.directory_env = std::mem::take(&mut config.directory_env)
+            .into_iter()
+            .map(|(dir, mut vars)| {
+                expand_env_values(&mut vars);
+                (directory_key(&dir), vars)
+            })
+            .collect();
+        for (name, template) in &mut config.templates {
+            if template.layout.is_some() && !template.commands.is_empty() {
+                bail!("template '{name}' cannot set both layout and commands");
+            }
+            template.directory = template.directory.as_deref().map(expand_tilde);
+            expand_env_values(&mut template.env);
+        }
+        Ok(config)
+    }
+
+    /// Loads the config file, returning the defaults when it does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        let input = match std::fs::read_to_string(path) {
+            Ok(input) => input,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        Self::parse(&input).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Applies CLI flags on top of the file config. Flags always win.
+    pub fn merge_args(mut self, args: &Args) -> Self {
+        if let Some(page_size) = args.page_size {
+            self.page_size = page_size;
+        }
+        if let Some(sort) = args.sort {
+            self.sort = sort;
+        }
+        set_flag(&mut self.guake, args.guake, args.no_guake);
+        set_flag(&mut self.reuse, args.reuse, args.no_reuse);
+        set_flag(&mut self.show_hidden, args.hidden, args.no_hidden);
+        set_flag(&mut self.no_banner, args.no_banner, args.show_banner);
+        self
+    }
+
+    /// Directory the directory prompt resolves relative input against.
+    pub fn base_dir(&self) -> PathBuf {
+        self.base_dir.clone().unwrap_or_else(xdg::home_dir)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string_pretty(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn parse_empty_returns_defaults() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parse_reads_all_options() {
+        let config = Config::parse(
+            r##"
+            page_size = 10
+            sort = "frecency"
+            guake = true
+            no_banner = true
+            reuse = true
+            show_hidden = true
+            default_action = "attach"
+            base_dir = "/srv"
+            project_roots = ["~/src", "/srv/work"]
+            project_depth = 2
+            naming = "{repo}-{branch}"
+            default_layout = "compact"
+            confirm = "never"
+
+            env_file = ".envrc"
+
+            [directory_layouts]
+            "~/work/backend" = "three-pane"
+
+            [directory_env."~/work"]
+            AWS_PROFILE = "work"
+            KUBECONFIG = "~/.kube/work"
+
+            [colors.attach]
+            prompt = "light_magenta"
+            highlight = "#102030"
+
+            [templates.oncall]
+            directory = "~/src/ops"
+            name = "{repo}-oncall"
+            env = { KUBECONFIG = "~/.kube/prod" }
+            commands = ["tail -f /var/log/syslog", "k9s"]
+            "##,
+        )
+        .unwrap();
+        assert_eq!(config.page_size, 10);
+        assert_eq!(config.sort, SortOrder::Frecency);
+        assert!(config.guake);
+        assert!(config.no_banner);
+        assert!(config.reuse);
+        assert!(config.show_hidden);
+        assert_eq!(config.default_action, Some(Action::Attach));
+        assert_eq!(config.base_dir, Some(PathBuf::from("/srv")));
+        assert_eq!(
+            config.project_roots,
+            vec![xdg::home_dir().join("src"), PathBuf::from("/srv/work")]
+        );
+        assert_eq!(config.project_depth, 2);
+        assert_eq!(
+            config.naming,
+            NamingStrategy::Template("{repo}-{branch}".to_string())
+        );
+        assert_eq!(config.default_layout, Some("compact".to_string()));
+        assert_eq!(
+            config.directory_layouts,
+            BTreeMap::from([(
+                xdg::home_dir().join("work/backend"),
+                "three-pane".to_string()
+            )])
+        );
+        assert_eq!(
+            config.directory_env,
+            BTreeMap::from([(
+                xdg::home_dir().join("work"),
+                BTreeMap::from([
+                    ("AWS_PROFILE".to_string(), "work".to_string()),
+                    (
+                        "KUBECONFIG".to_string(),
+                        xdg::home_dir().join(".kube/work").display().to_string()
+                    ),
+                ])
+            )])
+        );
+        assert_eq!(config.env_file, ".envrc");
+        assert_eq!(config.confirm, ConfirmPolicy::Never);
+        assert_eq!(
+            config.colors[&Action::Attach],
+            ActionColors {
+                prompt: Some(ColorName(Color::LightMagenta)),
+                highlight: Some(ColorName(Color::rgb(0x10, 0x20, 0x30))),
+            }
+        );
+        let oncall = &config.templates["oncall"];
+        assert_eq!(oncall.directory, Some(xdg::home_dir().join("src/ops")));
+        assert_eq!(
+            oncall.name,
+            Some(NamingStrategy::Template("{repo}-oncall".to_string()))
+        );
+        assert_eq!(
+            oncall.env["KUBECONFIG"],
+            xdg::home_dir().join(".kube/prod").to_string_lossy()
+        );
+        assert_eq!(oncall.commands, vec!["tail -f /var/log/syslog", "k9s"]);
+    }
+
+    #[test]
+    fn parse_rejects_template_with_layout_and_commands() {
+        let input = "[templates.oncall]\nlayout = \"compact\"\ncommands = [\"k9s\"]";
+        assert!(Config::parse(input).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_keys() {
+        assert!(Config::parse("pagesize = 10").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_colors() {
+        assert!(Config::parse("[colors.create]\nprompt = \"mauve\"").is_err());
+    }
+
+    #[test]
+    fn parse_rejects_zero_page_size() {
+        assert!(Config::parse("page_size = 0").is_err());
+    }
+
+    #[test]
+    fn parse_canonicalizes_directory_keys() {
+        let tmp = tempfile::tempdir().unwrap();
+        let real = tmp.path().join("real");
+        std::fs::create_dir(&real).unwrap();
+        let link = tmp.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        let input = format!(
+            "[directory_layouts]\n{:?} = \"compact\"\n[directory_env.{:?}]\nA = \"1\"",
+            link.display().to_string(),
+            link.display().to_string()
+        );
+        let config = Config::parse(&input).unwrap();
+        let real = real.canonicalize().unwrap();
+        assert!(config.directory_layouts.contains_key(&real));
+        assert!(config.directory_env.contains_key(&real));
+    }
+
+    #[test]
+    fn parse_rejects_zero_project_depth() {
+        assert!(Config::parse("project_depth = 0").is_err());
+    }
+
+    #[test]
+    fn parse_expands_tilde_in_base_dir() {
+        let config = Config::parse("base_dir = \"~/src\"").unwrap();
+        assert_eq!(config.base_dir, Some(xdg::home_dir().join("src")));
+    }
+
+    #[test]
+    fn confirm_policy_destructive_applies_only_to_kill_and_delete() {
+        assert!(ConfirmPolicy::Destructive.applies_to(Action::Delete));
+        assert!(ConfirmPolicy::Destructive.applies_to(Action::Kill));
+        assert!(!ConfirmPolicy::Destructive.applies_to(Action::Attach));
+        assert!(ConfirmPolicy::Always.applies_to(Action::Create));
+        assert!(!ConfirmPolicy::Never.applies_to(Action::Delete));
+    }
+
+    #[test]
+    fn load_returns_defaults_when_file_is_missing() {
+        let config = Config::load(Path::new("/nonexistent/zism/config.toml")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn merge_args_lets_flags_override_file() {
+        let config = Config::parse("page_size = 10").unwrap();
+        let args = Args::parse_from([
+            "zism",
+            "--page-size",
+            "5",
+            "--guake",
+            "--sort",
+            "created",
+            "--hidden",
+        ]);
+        let config = config.merge_args(&args);
+        assert_eq!(config.page_size, 5);
+        assert_eq!(config.sort, SortOrder::Created);
+        assert!(config.guake);
+        assert!(config.show_hidden);
+        assert!(!config.no_banner);
+    }
+
+    #[test]
+    fn merge_args_negations_override_file() {
+        let config = Config::parse("guake = true\nreuse = true\nshow_hidden = true").unwrap();
+        let args = Args::parse_from(["zism", "--no-guake", "--no-reuse", "--no-hidden"]);
+        let config = config.merge_args(&args);
+        assert!(!config.guake);
+        assert!(!config.reuse);
+        assert!(!config.show_hidden);
+    }
+
+    #[test]
+    fn merge_args_show_banner_overrides_file() {
+        let config = Config::parse("no_banner = true").unwrap();
+        let config = config.merge_args(&Args::parse_from(["zism", "--show-banner"]));
+        assert!(!config.no_banner);
+        let config = config.merge_args(&Args::parse_from(["zism", "--no-banner"]));
+        assert!(config.no_banner);
+    }
+
+    #[test]
+    fn merge_args_keeps_file_values_without_flags() {
+        let config = Config::parse("page_size = 10\nno_banner = true").unwrap();
+        let config = config.merge_args(&Args::parse_from(["zism"]));
+        assert_eq!(config.page_size, 10);
+        assert!(config.no_banner);
+    }
+
+    #[test]
+    fn to_toml_round_trips() {
+        let config = Config::parse(
+            "default_action = \"create-with-dir\"\nnaming = \"repo\"\n[colors.delete]\nprompt = \"208\"",
+        )
+        .unwrap();
+        assert_eq!(Config::parse(&config.to_toml().unwrap()).unwrap(), config);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    value
+}
+
+/// Parses a `.env`-style file: `KEY=VALUE` lines with optional `export`,
+/// quoted values, blank lines and `#` comments. No variable expansion.
+pub fn parse_env_file(input: &str) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for (number, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected KEY=VALUE", number + 1);
+        };
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            bail!("line {}: invalid variable name '{key}'", number + 1);
+        }
+        vars.insert(key.to_string(), unquote(value.trim()).to_string());
+    }
+    Ok(vars)
+}
+
+/// Loads `file_name` from `dir`, returning nothing when it does not exist.
+pub fn load_env_file(dir: &Path, file_name: &str) -> Result<BTreeMap<String, String>> {
+    let path = dir.join(file_name);
+    match std::fs::read_to_string(&path) {
+        Ok(input) => {
+            parse_env_file(&input).with_context(|| format!("Invalid env file {}", path.display()))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Variables configured for `dir`. Every matching prefix contributes, and
+/// longer prefixes override shorter ones.
+pub fn env_for_dir(
+    overrides: &BTreeMap<PathBuf, BTreeMap<String, String>>,
+    dir: &Path,
+) -> BTreeMap<String, String> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    let mut matching: Vec<_> = overrides
+        .iter()
+        .filter(|(prefix, _)| dir.starts_with(prefix))
+        .collect();
+    matching.sort_by_key(|(prefix, _)| prefix.components().count());
+    matching
+        .into_iter()
+        .flat_map(|(_, vars)| vars.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_env_file_reads_assignments() {
+        let input = "# profile\nAWS_PROFILE=dev\n\nexport NODE_ENV = \"production\"\nGREETING='hi there'\nEMPTY=\n";
+        assert_eq!(
+            parse_env_file(input).unwrap(),
+            vars(&[
+                ("AWS_PROFILE", "dev"),
+                ("NODE_ENV", "production"),
+                ("GREETING", "hi there"),
+                ("EMPTY", ""),
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_env_file_keeps_equals_in_values() {
+        assert_eq!(
+            parse_env_file("URL=http://x?a=b").unwrap(),
+            vars(&[("URL", "http://x?a=b")])
+        );
+    }
+
+    #[test]
+    fn parse_env_file_rejects_lines_without_assignment() {
+        assert!(parse_env_file("AWS_PROFILE").is_err());
+        assert!(parse_env_file("MY VAR=1").is_err());
+    }
+
+    #[test]
+    fn load_env_file_returns_empty_when_missing() {
+        let vars = load_env_file(Path::new("/nonexistent/zism"), ".env").unwrap();
+        assert!(vars.is_empty());
+    }
+
+    #[test]
+    fn env_for_dir_lets_longer_prefixes_override() {
+        let overrides = BTreeMap::from([
+            (
+                PathBuf::from("/work"),
+                vars(&[("AWS_PROFILE", "work"), ("NODE_ENV", "development")]),
+            ),
+            (
+                PathBuf::from("/work/prod"),
+                vars(&[("AWS_PROFILE", "prod")]),
+            ),
+            (PathBuf::from("/other"), vars(&[("KUBECONFIG", "other")])),
+        ]);
+        assert_eq!(
+            env_for_dir(&overrides, Path::new("/work/prod/api")),
+            vars(&[("AWS_PROFILE", "prod"), ("NODE_ENV", "development")])
+        );
+        assert!(env_for_dir(&overrides, Path::new("/home")).is_empty());
+    }
+}
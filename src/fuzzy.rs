@@ -0,0 +1,248 @@
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 24;
+const BOUNDARY_BONUS: i64 = 30;
+const PREFIX_BONUS: i64 = 20;
+const CASE_BONUS: i64 = 1;
+const GAP_PENALTY: i64 = 2;
+const LEADING_GAP_PENALTY: i64 = 1;
+
+const UNDERLINE: &str = "\x1b[4m";
+const NO_UNDERLINE: &str = "\x1b[24m";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub score: i64,
+    /// Char indices of `candidate` matched by the query, in ascending order.
+    pub positions: Vec<usize>,
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, '-' | '_' | ' ' | '/' | '.' | ':')
+}
+
+/// Bonus for matching at `index`: word starts after separators and
+/// lower-to-upper camelCase transitions.
+fn boundary_bonus(chars: &[char], index: usize) -> i64 {
+    if index == 0 {
+        return BOUNDARY_BONUS + PREFIX_BONUS;
+    }
+    let prev = chars[index - 1];
+    let cur = chars[index];
+    if is_separator(prev) || (prev.is_lowercase() && cur.is_uppercase()) {
+        BOUNDARY_BONUS
+    } else {
+        0
+    }
+}
+
+fn chars_eq(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Scores `candidate` against `query` as a case-insensitive subsequence.
+///
+/// Every matched char earns a base score, plus bonuses when it starts a word,
+/// starts the candidate, or directly follows the previous match. Skipped chars
+/// between matches cost a small penalty. Returns the best-scoring alignment,
+/// or `None` when `query` is not a subsequence of `candidate`.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<Match> {
+    let query: Vec<char> = query.chars().filter(|c| !c.is_whitespace()).collect();
+    let chars: Vec<char> = candidate.chars().collect();
+    if query.is_empty() {
+        return Some(Match {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    if query.len() > chars.len() {
+        return None;
+    }
+
+    // best[i][j]: best score for query[..=i] with query[i] matched at chars[j].
+    // from[i][j]: position of query[i - 1] in that alignment.
+    let mut best = vec![vec![None::<i64>; chars.len()]; query.len()];
+    let mut from = vec![vec![0usize; chars.len()]; query.len()];
+
+    for (i, &q) in query.iter().enumerate() {
+        for j in i..chars.len() {
+            if !chars_eq(q, chars[j]) {
+                continue;
+            }
+            let char_score = MATCH_SCORE
+                + boundary_bonus(&chars, j)
+                + if q == chars[j] { CASE_BONUS } else { 0 };
+
+            if i == 0 {
+                best[i][j] = Some(char_score - LEADING_GAP_PENALTY * j as i64);
+                continue;
+            }
+
+            for k in (i - 1)..j {
+                let Some(prev) = best[i - 1][k] else {
+                    continue;
+                };
+                let gap = (j - k - 1) as i64;
+                let link = if gap == 0 {
+                    CONSECUTIVE_BONUS
+                } else {
+                    -GAP_PENALTY * gap
+                };
+                let score = prev + char_score + link;
+                if best[i][j].is_none_or(|b| score > b) {
+                    best[i][j] = Some(score);
+                    from[i][j] = k;
+                }
+            }
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut j, score) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, s)| s.map(|s| (j, s)))
+        .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+
+    let mut positions = vec![0; query.len()];
+    for i in (0..query.len()).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+
+    Some(Match { score, positions })
+}
+
+/// Orders `(index, score)` pairs best score first; ties keep their original
+/// order.
+pub fn sorter(options: &mut [(usize, i64)]) {
+    options.sort_by_key(|&(index, score)| (std::cmp::Reverse(score), index));
+}
+
+/// Underlines the chars of `label` matched by `query`.
+pub fn highlight(query: &str, label: &str) -> String {
+    let Some(m) = fuzzy_match(query, label).filter(|m| !m.positions.is_empty()) else {
+        return label.to_string();
+    };
+
+    let mut out = String::with_capacity(label.len() + m.positions.len() * 8);
+    let mut positions = m.positions.iter().peekable();
+    let mut underlined = false;
+    for (i, c) in label.chars().enumerate() {
+        let matched = positions.next_if_eq(&&i).is_some();
+        if matched != underlined {
+            out.push_str(if matched { UNDERLINE } else { NO_UNDERLINE });
+            underlined = matched;
+        }
+        out.push(c);
+    }
+    if underlined {
+        out.push_str(NO_UNDERLINE);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(query: &str, candidate: &str) -> i64 {
+        fuzzy_match(query, candidate).unwrap().score
+    }
+
+    fn rank<'a>(query: &str, candidates: &[&'a str]) -> Vec<&'a str> {
+        let mut scored: Vec<_> = candidates
+            .iter()
+            .filter_map(|c| fuzzy_match(query, c).map(|m| (m.score, *c)))
+            .collect();
+        scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        scored.into_iter().map(|(_, c)| c).collect()
+    }
+
+    #[test]
+    fn empty_query_matches_everything() {
+        assert_eq!(
+            fuzzy_match("", "api"),
+            Some(Match {
+                score: 0,
+                positions: vec![]
+            })
+        );
+    }
+
+    #[test]
+    fn non_subsequence_does_not_match() {
+        assert_eq!(fuzzy_match("xyz", "api-dev"), None);
+        assert_eq!(fuzzy_match("ved", "api-dev"), None);
+        assert_eq!(fuzzy_match("long-query", "short"), None);
+    }
+
+    #[test]
+    fn matching_is_case_insensitive() {
+        assert!(fuzzy_match("API", "api-dev").is_some());
+        assert!(fuzzy_match("api", "API-dev").is_some());
+    }
+
+    #[test]
+    fn exact_case_scores_higher() {
+        assert!(score("Api", "Api") > score("Api", "api"));
+    }
+
+    #[test]
+    fn prefers_word_boundaries() {
+        assert_eq!(fuzzy_match("ap", "api-prod").unwrap().positions, vec![0, 1]);
+        assert_eq!(
+            fuzzy_match("aph", "api-prod-hotfix").unwrap().positions,
+            vec![0, 4, 9]
+        );
+    }
+
+    #[test]
+    fn prefix_beats_inner_word_start() {
+        assert!(score("api", "api-dev") > score("api", "my-api"));
+    }
+
+    #[test]
+    fn consecutive_beats_scattered() {
+        assert!(score("dev", "api-dev") > score("dev", "deliver"));
+    }
+
+    #[test]
+    fn camel_case_counts_as_boundary() {
+        assert_eq!(fuzzy_match("fb", "fooBar").unwrap().positions, vec![0, 3]);
+    }
+
+    #[test]
+    fn ranks_similar_session_names_predictably() {
+        let sessions = ["api-prod-hotfix", "api-staging", "api-dev", "rapid"];
+        assert_eq!(
+            rank("apd", &sessions),
+            vec!["api-dev", "api-prod-hotfix", "rapid"]
+        );
+        assert_eq!(
+            rank("ap", &sessions),
+            vec!["api-prod-hotfix", "api-staging", "api-dev", "rapid"]
+        );
+    }
+
+    #[test]
+    fn whitespace_in_query_is_ignored() {
+        assert_eq!(score("api dev", "api-dev"), score("apidev", "api-dev"));
+    }
+
+    #[test]
+    fn sorter_orders_by_score_then_index() {
+        let mut options = vec![(0, 10), (1, 30), (2, 10), (3, 30)];
+        sorter(&mut options);
+        assert_eq!(options, vec![(1, 30), (3, 30), (0, 10), (2, 10)]);
+    }
+
+    #[test]
+    fn highlight_underlines_matched_chars() {
+        assert_eq!(
+            highlight("ad", "api-dev"),
+            format!("{UNDERLINE}a{NO_UNDERLINE}pi-{UNDERLINE}d{NO_UNDERLINE}ev")
+        );
+        assert_eq!(highlight("", "api-dev"), "api-dev");
+        assert_eq!(highlight("xyz", "api-dev"), "api-dev");
+    }
+}
@@ -0,0 +1,29 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Runs `git -C <dir> <args>` and returns its trimmed stdout, or `None` when
+/// git is missing, fails, or prints nothing.
+fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!stdout.is_empty()).then_some(stdout)
+}
+
+/// Root of the working tree containing `dir`.
+pub fn toplevel(dir: &Path) -> Option<PathBuf> {
+    git_output(dir, &["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+/// Checked-out branch, or the short commit hash when HEAD is detached.
+pub fn current_branch(dir: &Path) -> Option<String> {
+    git_output(dir, &["branch", "--show-current"])
+        .or_else(|| git_output(dir, &["rev-parse", "--short", "HEAD"]))
+}
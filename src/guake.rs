@@ -19,19 +19,25 @@ mod tests {
     use serial_test::serial;
 
     use super::*;
+    use crate::testutil::restore_var;
 
     #[test]
     #[serial]
     fn is_inside_guake_returns_true_when_env_set() {
+        let previous = std::env::var_os("GUAKE_TAB_UUID");
         unsafe { std::env::set_var("GUAKE_TAB_UUID", "some-uuid") };
-        assert!(is_inside_guake());
-        unsafe { std::env::remove_var("GUAKE_TAB_UUID") };
+        let inside = is_inside_guake();
+        restore_var("GUAKE_TAB_UUID", previous);
+        assert!(inside);
     }
 
     #[test]
     #[serial]
     fn is_inside_guake_returns_false_when_env_unset() {
+        let previous = std::env::var_os("GUAKE_TAB_UUID");
         unsafe { std::env::remove_var("GUAKE_TAB_UUID") };
-        assert!(!is_inside_guake());
+        let inside = is_inside_guake();
+        restore_var("GUAKE_TAB_UUID", previous);
+        assert!(!inside);
     }
 }
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::{git, xdg};
+
+/// Layouts compiled into Zellij.
+pub const BUILTIN_LAYOUTS: &[&str] = &["default", "compact", "strider", "classic"];
+
+/// Layout files a project can check in, in order of preference.
+pub const PROJECT_LAYOUT_FILES: &[&str] = &[".zellij/layout.kdl", "zism.kdl"];
+
+fn project_layout_in(dir: &Path) -> Option<PathBuf> {
+    PROJECT_LAYOUT_FILES
+        .iter()
+        .map(|file| dir.join(file))
+        .find(|path| path.is_file())
+}
+
+/// Project-specific layout for `dir`, looked up in `dir` itself and then in
+/// the root of the git repository containing it.
+pub fn find_project_layout(dir: &Path) -> Option<PathBuf> {
+    project_layout_in(dir).or_else(|| {
+        let root = git::toplevel(dir)?;
+        let dir = dir.canonicalize().ok();
+        (dir.as_deref() != Some(root.as_path()))
+            .then(|| project_layout_in(&root))
+            .flatten()
+    })
+}
+
+/// Zellij's config directory: `$ZELLIJ_CONFIG_DIR`, else
+/// `$XDG_CONFIG_HOME/zellij`.
+pub fn zellij_config_dir() -> PathBuf {
+    std::env::var_os("ZELLIJ_CONFIG_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| xdg::config_home().join("zellij"))
+}
+
+/// The `layout_dir` option from Zellij's `config.kdl`, if set.
+fn parse_layout_dir(config: &str) -> Option<PathBuf> {
+    config.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("layout_dir")?;
+        let value = rest.strip_prefix(char::is_whitespace)?.trim();
+        let value = value.strip_prefix('"')?;
+        let end = value.find('"')?;
+        Some(PathBuf::from(&value[..end]))
+    })
+}
+
+/// Directory Zellij loads layouts from: its `layout_dir` option, relative to
+/// `config_dir` when not absolute, else `<config_dir>/layouts`.
+fn layouts_dir(config_dir: &Path) -> PathBuf {
+    std::fs::read_to_string(config_dir.join("config.kdl"))
+        .ok()
+        .and_then(|config| parse_layout_dir(&config))
+        .map(|dir| config_dir.join(dir))
+        .unwrap_or_else(|| config_dir.join("layouts"))
+}
+
+/// Layout names from Zellij's layout directory (`*.kdl`), followed by the
+/// built-ins they do not shadow. Swap layouts (`*.swap.kdl`) are skipped.
+pub fn discover_layouts(config_dir: &Path) -> Vec<String> {
+    let mut layouts: Vec<String> = std::fs::read_dir(layouts_dir(config_dir))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let stem = name.strip_suffix(".kdl")?;
+            (!stem.ends_with(".swap")).then(|| stem.to_string())
+        })
+        .collect();
+    layouts.sort();
+    for builtin in BUILTIN_LAYOUTS {
+        if !layouts.iter().any(|l| l == builtin) {
+            layouts.push(builtin.to_string());
+        }
+    }
+    layouts
+}
+
+/// Layout configured for `dir`: the override whose directory is the
+/// longest prefix of `dir`.
+pub fn layout_for_dir<'a>(overrides: &'a BTreeMap<PathBuf, String>, dir: &Path) -> Option<&'a str> {
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    overrides
+        .iter()
+        .filter(|(prefix, _)| dir.starts_with(prefix))
+        .max_by_key(|(prefix, _)| prefix.components().count())
+        .map(|(_, layout)| layout.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discover_layouts_lists_user_layouts_then_builtins() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        let layouts_dir = dir.join("layouts");
+        std::fs::create_dir_all(&layouts_dir).unwrap();
+        for file in [
+            "three-pane.kdl",
+            "compact.kdl",
+            "three-pane.swap.kdl",
+            "notes.txt",
+        ] {
+            std::fs::write(layouts_dir.join(file), "layout {}").unwrap();
+        }
+        assert_eq!(
+            discover_layouts(&dir),
+            vec!["compact", "three-pane", "default", "strider", "classic"]
+        );
+    }
+
+    #[test]
+    fn discover_layouts_follows_layout_dir_option() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(dir.join("mine")).unwrap();
+        std::fs::write(dir.join("mine/work.kdl"), "layout {}").unwrap();
+        std::fs::write(dir.join("config.kdl"), "layout_dir \"mine\"\n").unwrap();
+        assert_eq!(discover_layouts(&dir)[0], "work");
+    }
+
+    #[test]
+    fn parse_layout_dir_reads_the_option() {
+        assert_eq!(
+            parse_layout_dir("theme \"dracula\"\n  layout_dir \"/etc/zellij/layouts\"\n"),
+            Some(PathBuf::from("/etc/zellij/layouts"))
+        );
+        assert_eq!(
+            parse_layout_dir("// layout_dir \"/x\"\nlayout_directory \"/y\""),
+            None
+        );
+    }
+
+    #[test]
+    fn discover_layouts_returns_builtins_without_layouts_dir() {
+        assert_eq!(
+            discover_layouts(Path::new("/nonexistent/zellij")),
+            BUILTIN_LAYOUTS.to_vec()
+        );
+    }
+
+    #[test]
+    fn find_project_layout_prefers_dot_zellij_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(dir.join(".zellij")).unwrap();
+        std::fs::write(dir.join(".zellij/layout.kdl"), "layout {}").unwrap();
+        std::fs::write(dir.join("zism.kdl"), "layout {}").unwrap();
+        assert_eq!(
+            find_project_layout(&dir),
+            Some(dir.join(".zellij/layout.kdl"))
+        );
+    }
+
+    #[test]
+    fn find_project_layout_finds_zism_kdl() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::write(dir.join("zism.kdl"), "layout {}").unwrap();
+        assert_eq!(find_project_layout(&dir), Some(dir.join("zism.kdl")));
+    }
+
+    #[test]
+    fn find_project_layout_returns_none_without_layout() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        assert_eq!(find_project_layout(&dir), None);
+    }
+
+    #[test]
+    fn layout_for_dir_prefers_longest_prefix() {
+        let overrides = BTreeMap::from([
+            (PathBuf::from("/srv"), "default".to_string()),
+            (PathBuf::from("/srv/backend"), "three-pane".to_string()),
+        ]);
+        assert_eq!(
+            layout_for_dir(&overrides, Path::new("/srv/backend/api")),
+            Some("three-pane")
+        );
+        assert_eq!(
+            layout_for_dir(&overrides, Path::new("/srv/web")),
+            Some("default")
+        );
+        assert_eq!(layout_for_dir(&overrides, Path::new("/home")), None);
+    }
+}
@@ -1,94 +1,643 @@
 mod action;
 mod banner;
+mod cli;
+mod config;
+mod env;
+mod fuzzy;
+mod git;
 mod guake;
+mod layout;
+mod naming;
+mod output;
+mod paths;
+mod picker;
+mod projects;
+mod registry;
+mod sort;
+mod template;
+#[cfg(test)]
+mod testutil;
 mod ui;
+mod xdg;
 mod zellij;
+mod zoxide;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 use clap::Parser;
 
 use action::Action;
+use cli::{Args, Command, ConfigCommand};
+use config::Config;
+use registry::Registry;
 
-#[derive(Parser)]
-#[command(version)]
-struct Args {
-    /// Number of candidates to display at once
-    #[arg(long, default_value_t = 24)]
-    page_size: usize,
+/// Page size cap for the popup, which runs in a small floating pane.
+const POPUP_PAGE_SIZE: usize = 10;
 
-    /// Rename Guake tab to session name on create/attach
-    #[arg(long)]
-    guake: bool,
+/// Name of the Zellij session zism runs in, if any.
+fn current_session() -> Option<String> {
+    std::env::var("ZELLIJ_SESSION_NAME").ok()
+}
 
-    /// Print banner and exit
-    #[arg(long, conflicts_with = "no_banner")]
-    banner: bool,
+fn is_inside_zellij() -> bool {
+    current_session().is_some()
+}
 
-    /// Suppress banner display
-    #[arg(long, conflicts_with = "banner")]
-    no_banner: bool,
+fn rename_guake_tab(config: &Config, name: &str) -> Result<()> {
+    if config.guake && guake::is_inside_guake() {
+        guake::rename_tab(name)?;
+    }
+    Ok(())
 }
 
-fn is_inside_zellij() -> bool {
-    std::env::var("ZELLIJ_SESSION_NAME").is_ok()
+/// Asks for confirmation when the configured policy covers `action`.
+fn confirmed(config: &Config, action: Action, message: &str) -> Result<bool> {
+    if config.confirm.applies_to(action) {
+        ui::confirm(message, action)
+    } else {
+        Ok(true)
+    }
 }
 
-fn run(args: &Args) -> Result<()> {
-    if !args.no_banner {
-        banner::print_banner();
+/// `names` without the session zism runs in, which cannot be killed or
+/// deleted from inside itself. Skipping it is reported as a warning.
+fn without_current_session(names: &[String], verb: &str) -> Vec<String> {
+    let current = current_session();
+    names
+        .iter()
+        .filter(|name| {
+            let is_current = current.as_deref() == Some(name.as_str());
+            if is_current {
+                eprintln!("Warning: not going to {verb} '{name}', the session zism runs in");
+            }
+            !is_current
+        })
+        .cloned()
+        .collect()
+}
+
+/// Runs `op` on every session, reporting each result, and fails if any
+/// failed. `verb` and `past` are e.g. "delete" and "Deleted".
+fn for_each_session(
+    names: &[String],
+    verb: &str,
+    past: &str,
+    op: impl Fn(&str) -> Result<()>,
+) -> Result<()> {
+    let mut failed = 0;
+    for name in names {
+        match op(name) {
+            Ok(()) => println!("{past} session '{name}'"),
+            Err(err) => {
+                eprintln!("{err}");
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("Failed to {verb} {failed} of {} session(s)", names.len());
     }
+    Ok(())
+}
 
-    if args.banner {
+/// Loads the session registry. Registry problems never block session
+/// management, so errors are reported and an empty registry is used.
+fn load_registry() -> Registry {
+    let path = registry::registry_path();
+    Registry::load(&path).unwrap_or_else(|err| {
+        eprintln!("Warning: {err:#}");
+        Registry::default()
+    })
+}
+
+fn update_registry(update: impl FnOnce(&mut Registry)) {
+    let mut registry = load_registry();
+    update(&mut registry);
+    if let Err(err) = registry.save() {
+        eprintln!("Warning: {err:#}");
+    }
+}
+
+// The wrappers below record the session in the registry. Inside Zellij they
+// switch the current client instead of starting a nested one, and record only
+// once that worked. Outside Zellij they record before handing the process over
+// with `exec`, which never returns on success; if the exec itself fails, the
+// record stays behind, which only skews the session's age and sort position.
+
+fn create_session(
+    config: &Config,
+    name: &str,
+    layout: Option<&str>,
+    env: &BTreeMap<String, String>,
+) -> Result<()> {
+    if is_inside_zellij() {
+        zellij::create_session_in_background(name, None, layout, env)?;
+        update_registry(|r| r.record_created(name, None, layout, registry::now()));
+        return zellij::switch_session(name);
+    }
+    rename_guake_tab(config, name)?;
+    update_registry(|r| r.record_created(name, None, layout, registry::now()));
+    zellij::create_session(name, layout, env)
+}
+
+fn create_session_with_dir(
+    config: &Config,
+    name: &str,
+    dir: &Path,
+    layout: Option<&str>,
+    env: &BTreeMap<String, String>,
+) -> Result<()> {
+    if is_inside_zellij() {
+        zellij::create_session_in_background(name, Some(dir), layout, env)?;
+        update_registry(|r| r.record_created(name, Some(dir), layout, registry::now()));
+        return zellij::switch_session(name);
+    }
+    rename_guake_tab(config, name)?;
+    update_registry(|r| r.record_created(name, Some(dir), layout, registry::now()));
+    zellij::create_session_with_dir(name, dir, layout, env)
+}
+
+fn attach_session(config: &Config, name: &str) -> Result<()> {
+    if is_inside_zellij() {
+        zellij::switch_session(name)?;
+        update_registry(|r| r.record_attached(name, registry::now()));
         return Ok(());
     }
+    rename_guake_tab(config, name)?;
+    update_registry(|r| r.record_attached(name, registry::now()));
+    zellij::attach_session(name)
+}
 
+/// Fails unless Zellij knows a session called `name`, so nothing is
+/// recorded for a mistyped name.
+fn ensure_session_exists(name: &str) -> Result<()> {
+    if !zellij::list_sessions()?.iter().any(|s| s.name == name) {
+        bail!("No session named '{name}'");
+    }
+    Ok(())
+}
+
+fn resurrect_session(config: &Config, name: &str, force_run_commands: bool) -> Result<()> {
     if is_inside_zellij() {
-        eprintln!("Already inside a Zellij session. Please run zism from outside Zellij.");
-        std::process::exit(1);
+        zellij::switch_session(name)?;
+        update_registry(|r| r.record_attached(name, registry::now()));
+        return Ok(());
+    }
+    rename_guake_tab(config, name)?;
+    update_registry(|r| r.record_attached(name, registry::now()));
+    zellij::resurrect_session(name, force_run_commands)
+}
+
+fn delete_session(name: &str, force: bool) -> Result<()> {
+    zellij::delete_session(name, force)?;
+    update_registry(|r| r.remove(name));
+    Ok(())
+}
+
+/// Layout configured for `dir` without asking: a layout file checked into
+/// the project, else a per-directory override from the config.
+fn configured_layout(config: &Config, dir: Option<&Path>) -> Option<String> {
+    let dir = dir?;
+    layout::find_project_layout(dir)
+        .map(|path| path.display().to_string())
+        .or_else(|| layout::layout_for_dir(&config.directory_layouts, dir).map(str::to_string))
+}
+
+/// Layout for a new session: a project or per-directory layout if one
+/// matches, otherwise whatever the user picks.
+fn pick_layout(config: &Config, dir: Option<&Path>, action: Action) -> Result<Option<String>> {
+    if let Some(layout) = configured_layout(config, dir) {
+        return Ok(Some(layout));
+    }
+    let layouts = layout::discover_layouts(&layout::zellij_config_dir());
+    ui::select_layout(
+        &layouts,
+        config.default_layout.as_deref(),
+        config.page_size,
+        action,
+    )
+}
+
+fn describe_layout(layout: Option<&str>) -> String {
+    layout.map_or(String::new(), |l| format!(" with layout '{l}'"))
+}
+
+/// Variables from the configured env file in `dir`. A file that cannot be
+/// read or parsed is skipped with a warning.
+fn env_file_vars(config: &Config, dir: &Path) -> BTreeMap<String, String> {
+    if config.env_file.is_empty() {
+        return BTreeMap::new();
+    }
+    env::load_env_file(dir, &config.env_file).unwrap_or_else(|err| {
+        eprintln!("Warning: {err:#}; ignoring it");
+        BTreeMap::new()
+    })
+}
+
+/// Environment for a new session in `dir`: per-directory variables from the
+/// config, overridden by `file_vars` from the env file, overridden by `extra`.
+fn session_env(
+    config: &Config,
+    dir: &Path,
+    file_vars: &BTreeMap<String, String>,
+    extra: &BTreeMap<String, String>,
+) -> BTreeMap<String, String> {
+    let mut vars = env::env_for_dir(&config.directory_env, dir);
+    vars.extend(file_vars.clone());
+    vars.extend(extra.clone());
+    vars
+}
+
+/// Asks before creating a session from files checked into the project, which
+/// can start arbitrary commands (a layout) or change how every command runs
+/// (`PATH`, `LD_PRELOAD`), and follows the confirm policy otherwise.
+fn confirmed_project_files(
+    config: &Config,
+    action: Action,
+    message: &str,
+    dir: &Path,
+    file_vars: &BTreeMap<String, String>,
+) -> Result<bool> {
+    if layout::find_project_layout(dir).is_some() || !file_vars.is_empty() {
+        ui::confirm(message, action)
+    } else {
+        confirmed(config, action, message)
+    }
+}
+
+fn describe_env(env: &BTreeMap<String, String>) -> String {
+    if env.is_empty() {
+        return String::new();
+    }
+    let names: Vec<_> = env.keys().map(String::as_str).collect();
+    format!(" and env {}", names.join(", "))
+}
+
+/// Directory for a new session: a discovered project when project roots are
+/// configured, otherwise (or on request) a typed path.
+fn choose_directory(config: &Config, action: Action) -> Result<PathBuf> {
+    if !config.project_roots.is_empty() {
+        let projects = projects::discover_cached(
+            &config.project_roots,
+            config.project_depth,
+            &projects::cache_path(),
+        );
+        if let Some(dir) = ui::select_project(&projects, config.page_size, action)? {
+            return Ok(dir);
+        }
+    }
+    ui::input_directory(
+        config.page_size,
+        &config.base_dir(),
+        &zoxide::ranked_dirs(),
+        config.show_hidden,
+        action,
+    )
+}
+
+fn run(args: &Args, config: &Config) -> Result<()> {
+    match &args.command {
+        Some(command) => run_command(config, command),
+        None => run_interactive(args, config),
+    }
+}
+
+fn run_command(config: &Config, command: &Command) -> Result<()> {
+    match command {
+        Command::List { format } => {
+            let sessions = zellij::list_sessions()?;
+            let registry = load_registry();
+            let entries: Vec<_> = sessions
+                .iter()
+                .map(|session| output::SessionEntry::new(session, registry.get(&session.name)))
+                .collect();
+            print!("{}", output::render_sessions(&entries, *format)?);
+        }
+        Command::Attach { name } => {
+            // Checked first, as the attach is recorded before exec.
+            ensure_session_exists(name)?;
+            attach_session(config, name)?;
+        }
+        Command::New {
+            name,
+            dir,
+            layout,
+            project_layout,
+            env_file,
+        } => {
+            let dir = dir.as_deref().map(paths::existing_dir).transpose()?;
+            let sessions = zellij::list_sessions()?;
+            if let Some(dir) = &dir
+                && config.reuse
+                && let Some(session) = load_registry().live_session_for_dir(dir, &sessions)
+            {
+                return attach_session(config, &session.name);
+            }
+            let existing: Vec<_> = sessions.into_iter().map(|s| s.name).collect();
+            if let Err(err) = zellij::validate_session_name(name, &existing) {
+                bail!("Invalid session name '{name}': {err}");
+            }
+            // A checked-in layout can run any command, and nobody is asked
+            // here, so it is only used on request.
+            let project = dir
+                .as_deref()
+                .filter(|_| *project_layout)
+                .and_then(layout::find_project_layout)
+                .map(|path| path.display().to_string());
+            let layout = layout
+                .clone()
+                .or(project)
+                .or_else(|| {
+                    let dir = dir.as_deref()?;
+                    layout::layout_for_dir(&config.directory_layouts, dir).map(str::to_string)
+                })
+                .or_else(|| config.default_layout.clone());
+            match &dir {
+                Some(dir) => {
+                    // Like the project layout, a checked-in env file (which
+                    // may set PATH or LD_PRELOAD) is only read on request.
+                    let file_vars = match env_file {
+                        true => env_file_vars(config, dir),
+                        false => BTreeMap::new(),
+                    };
+                    let env = session_env(config, dir, &file_vars, &BTreeMap::new());
+                    create_session_with_dir(config, name, dir, layout.as_deref(), &env)?
+                }
+                None => create_session(config, name, layout.as_deref(), &BTreeMap::new())?,
+            }
+        }
+        Command::Info { name } => {
+            let sessions = zellij::list_sessions()?;
+            let session = sessions.iter().find(|s| s.name == *name);
+            let registry = load_registry();
+            print!("{}", output::render_info(name, session, registry.get(name)));
+        }
+        Command::Tag { name, tags, remove } => {
+            ensure_session_exists(name)?;
+            update_registry(|r| {
+                if *remove {
+                    r.remove_tags(name, tags);
+                } else {
+                    r.add_tags(name, tags);
+                }
+            });
+        }
+        Command::Kill { names } => {
+            let names = without_current_session(names, "kill");
+            for_each_session(&names, "kill", "Killed", zellij::kill_session)?;
+        }
+        Command::Delete { names, force } => {
+            let names = without_current_session(names, "delete");
+            for_each_session(&names, "delete", "Deleted", |name| {
+                delete_session(name, *force)
+            })?;
+        }
+        Command::Popup => run_popup(config)?,
+        Command::Config {
+            command: ConfigCommand::Show,
+        } => {
+            println!("# {}", config::config_path().display());
+            print!("{}", config.to_toml()?);
+        }
     }
 
+    Ok(())
+}
+
+fn run_interactive(args: &Args, config: &Config) -> Result<()> {
+    if args.banner {
+        banner::print_banner();
+        return Ok(());
+    }
+
+    if !config.no_banner {
+        banner::print_banner();
+    }
+
+    run_actions(config)
+}
+
+fn is_cancellation(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<inquire::InquireError>(),
+        Some(
+            inquire::InquireError::OperationCanceled | inquire::InquireError::OperationInterrupted
+        )
+    )
+}
+
+/// Runs the interactive flow in a floating pane launched from a Zellij
+/// keybinding. Cancelling exits cleanly, so a pane started with
+/// `close_on_exit` closes and focus goes back to the pane underneath.
+fn run_popup(config: &Config) -> Result<()> {
+    if !is_inside_zellij() {
+        bail!("zism popup runs inside Zellij; use plain `zism` elsewhere");
+    }
+    let config = Config {
+        no_banner: true,
+        page_size: config.page_size.min(POPUP_PAGE_SIZE),
+        ..config.clone()
+    };
+    match run_actions(&config) {
+        Err(err) if is_cancellation(&err) => Ok(()),
+        result => result,
+    }
+}
+
+fn run_actions(config: &Config) -> Result<()> {
     let sessions = zellij::list_sessions()?;
-    let has_sessions = !sessions.is_empty();
+    let registry = load_registry();
 
-    let action = ui::select_action(has_sessions)?;
+    let action = ui::select_action(
+        &sessions,
+        !config.templates.is_empty(),
+        config.default_action,
+    )?;
 
     match action {
         Action::Create => {
-            let name = ui::input_session_name(action)?;
-            if args.guake && guake::is_inside_guake() {
-                guake::rename_tab(&name)?;
+            let name = ui::input_session_name(&sessions, action)?;
+            let layout = pick_layout(config, None, action)?;
+            let message = format!(
+                "Create session '{name}'{}?",
+                describe_layout(layout.as_deref())
+            );
+            if !confirmed(config, action, &message)? {
+                return Ok(());
             }
-            zellij::create_session(&name)?;
+            create_session(config, &name, layout.as_deref(), &BTreeMap::new())?;
         }
         Action::CreateWithDir => {
-            let cwd = ui::input_directory(args.page_size, action)?;
-            let name = cwd
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
+            let cwd = choose_directory(config, action)?;
+            if let Some(session) = registry.live_session_for_dir(&cwd, &sessions) {
+                let message = format!(
+                    "Session '{}' was created in this directory. Attach to it instead?",
+                    session.name
+                );
+                if config.reuse || ui::confirm(&message, Action::Attach)? {
+                    return attach_session(config, &session.name);
+                }
+            }
+            let derived = naming::derive_name(&config.naming, &cwd);
+            let name = ui::input_derived_session_name(&derived, &sessions, action)?;
+            let layout = pick_layout(config, Some(&cwd), action)?;
+            let file_vars = env_file_vars(config, &cwd);
+            let env = session_env(config, &cwd, &file_vars, &BTreeMap::new());
+            let message = format!(
+                "Create session '{name}' in {}{}{}?",
+                cwd.display(),
+                describe_layout(layout.as_deref()),
+                describe_env(&env)
+            );
+            if !confirmed_project_files(config, action, &message, &cwd, &file_vars)? {
+                return Ok(());
+            }
+            create_session_with_dir(config, &name, &cwd, layout.as_deref(), &env)?;
+        }
+        Action::FromTemplate => {
+            let (template_name, template) =
+                ui::select_template(&config.templates, config.page_size, action)?;
+            let dir = template
+                .directory
+                .as_deref()
+                .map(paths::existing_dir)
+                .transpose()?;
+            let derived = template.session_name(template_name);
+            let existing: Vec<_> = sessions.iter().map(|s| s.name.clone()).collect();
+            // Only ask for a name when the template's one is taken.
+            let name = if zellij::validate_session_name(&derived, &existing).is_ok() {
+                derived
+            } else {
+                ui::input_derived_session_name(&derived, &sessions, action)?
+            };
+            let file_vars = dir
+                .as_deref()
+                .map(|dir| env_file_vars(config, dir))
                 .unwrap_or_default();
-            if args.guake && guake::is_inside_guake() {
-                guake::rename_tab(&name)?;
+            let env = match &dir {
+                Some(dir) => session_env(config, dir, &file_vars, &template.env),
+                None => template.env.clone(),
+            };
+            let message = format!(
+                "Create session '{name}' from template '{template_name}'{}?",
+                describe_env(&env)
+            );
+            // The template's own layout is the user's config, not the project's,
+            // so only the env file forces the question here.
+            let proceed = if file_vars.is_empty() {
+                confirmed(config, action, &message)?
+            } else {
+                ui::confirm(&message, action)?
+            };
+            if !proceed {
+                return Ok(());
+            }
+            let layout = template.resolve_layout(template_name)?;
+            match &dir {
+                Some(dir) => create_session_with_dir(config, &name, dir, layout.as_deref(), &env)?,
+                None => create_session(config, &name, layout.as_deref(), &env)?,
             }
-            zellij::create_session_with_dir(&name, &cwd)?;
         }
         Action::Attach => {
-            let session = ui::select_session(&sessions, action)?;
-            if args.guake && guake::is_inside_guake() {
-                guake::rename_tab(&session)?;
+            let running: Vec<_> = sessions
+                .into_iter()
+                .filter(|s| !s.exited && !s.current)
+                .collect();
+            let session =
+                ui::select_session(&running, config.sort, &registry, config.page_size, action)?;
+            if !confirmed(config, action, &format!("Attach to '{}'?", session.name))? {
+                return Ok(());
             }
-            zellij::attach_session(&session)?;
+            attach_session(config, &session.name)?;
         }
-        Action::Delete => loop {
-            let sessions = zellij::list_sessions()?;
-            if sessions.is_empty() {
-                break;
+        Action::Resurrect => {
+            let exited: Vec<_> = sessions.into_iter().filter(|s| s.exited).collect();
+            let session =
+                ui::select_session(&exited, config.sort, &registry, config.page_size, action)?;
+            if !confirmed(config, action, &format!("Resurrect '{}'?", session.name))? {
+                return Ok(());
             }
-            let Some(session) = ui::select_session_optional(&sessions, action)? else {
-                break;
-            };
-            zellij::delete_session(&session)?;
-            println!("Deleted session '{session}'");
-        },
+            // Switching sessions has no way to force pane commands to run.
+            let force_run_commands = !is_inside_zellij() && ui::confirm_force_run_commands(action)?;
+            resurrect_session(config, &session.name, force_run_commands)?;
+        }
+        Action::Rename => {
+            let running: Vec<_> = sessions.iter().filter(|s| !s.exited).cloned().collect();
+            let session =
+                ui::select_session(&running, config.sort, &registry, config.page_size, action)?;
+            let new_name = ui::input_new_name(&session, &sessions, action)?;
+            if new_name == session.name {
+                return Ok(());
+            }
+            let message = format!("Rename '{}' to '{new_name}'?", session.name);
+            if !confirmed(config, action, &message)? {
+                return Ok(());
+            }
+            zellij::rename_session(&session.name, &new_name)?;
+            update_registry(|r| r.rename(&session.name, &new_name));
+            rename_guake_tab(config, &new_name)?;
+            println!("Renamed session '{}' to '{new_name}'", session.name);
+        }
+        Action::Kill => {
+            let running: Vec<_> = sessions
+                .into_iter()
+                .filter(|s| !s.exited && !s.current)
+                .collect();
+            let selected = ui::select_sessions(
+                &running,
+                false,
+                config.sort,
+                &registry,
+                config.page_size,
+                action,
+            )?;
+            if selected.is_empty() {
+                return Ok(());
+            }
+            let names: Vec<_> = selected.into_iter().map(|s| s.name).collect();
+            let message = format!("Kill {}?", names.join(", "));
+            if !confirmed(config, action, &message)? {
+                return Ok(());
+            }
+            for_each_session(&names, "kill", "Killed", zellij::kill_session)?;
+        }
+        Action::Delete => {
+            let others: Vec<_> = sessions.into_iter().filter(|s| !s.current).collect();
+            let filter = ui::select_session_filter(&others, action)?;
+            let candidates: Vec<_> = others.into_iter().filter(|s| filter.matches(s)).collect();
+            let preselect_all = filter == ui::SessionFilter::Exited;
+            let selected = ui::select_sessions(
+                &candidates,
+                preselect_all,
+                config.sort,
+                &registry,
+                config.page_size,
+                action,
+            )?;
+            if selected.is_empty() {
+                return Ok(());
+            }
+            println!("Sessions to delete:");
+            for session in &selected {
+                println!("  {} ({})", session.name, session.state());
+            }
+            let message = format!("Delete {} session(s)?", selected.len());
+            if !confirmed(config, action, &message)? {
+                return Ok(());
+            }
+            let running: Vec<_> = selected
+                .iter()
+                .filter(|s| !s.exited)
+                .map(|s| s.name.clone())
+                .collect();
+            let names: Vec<_> = selected.into_iter().map(|s| s.name).collect();
+            // Only running sessions need -f; exited ones are deleted as-is.
+            for_each_session(&names, "delete", "Deleted", |name| {
+                delete_session(name, running.iter().any(|r| r == name))
+            })?;
+        }
     }
 
     Ok(())
@@ -96,7 +645,9 @@ fn run(args: &Args) -> Result<()> {
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    run(&args)
+    let config = Config::load(&config::config_path())?.merge_args(&args);
+    action::set_colors(config.colors.clone());
+    run(&args, &config)
 }
 
 #[cfg(test)]
@@ -104,19 +655,56 @@ mod tests {
     use serial_test::serial;
 
     use super::*;
+    use crate::testutil::restore_var;
 
     #[test]
     #[serial]
     fn is_inside_zellij_returns_true_when_env_set() {
+        let previous = std::env::var_os("ZELLIJ_SESSION_NAME");
         unsafe { std::env::set_var("ZELLIJ_SESSION_NAME", "test") };
-        assert!(is_inside_zellij());
-        unsafe { std::env::remove_var("ZELLIJ_SESSION_NAME") };
+        let inside = is_inside_zellij();
+        restore_var("ZELLIJ_SESSION_NAME", previous);
+        assert!(inside);
     }
 
     #[test]
     #[serial]
     fn is_inside_zellij_returns_false_when_env_unset() {
+        let previous = std::env::var_os("ZELLIJ_SESSION_NAME");
+        unsafe { std::env::remove_var("ZELLIJ_SESSION_NAME") };
+        let inside = is_inside_zellij();
+        restore_var("ZELLIJ_SESSION_NAME", previous);
+        assert!(!inside);
+    }
+
+    #[test]
+    #[serial]
+    fn without_current_session_skips_the_session_zism_runs_in() {
+        let names = vec!["api".to_string(), "web".to_string()];
+        let previous = std::env::var_os("ZELLIJ_SESSION_NAME");
+        unsafe { std::env::set_var("ZELLIJ_SESSION_NAME", "api") };
+        let inside = without_current_session(&names, "kill");
+        unsafe { std::env::remove_var("ZELLIJ_SESSION_NAME") };
+        let outside = without_current_session(&names, "kill");
+        restore_var("ZELLIJ_SESSION_NAME", previous);
+        assert_eq!(inside, vec!["web"]);
+        assert_eq!(outside, names);
+    }
+
+    #[test]
+    fn is_cancellation_detects_escaped_prompts() {
+        let err = anyhow::Error::from(inquire::InquireError::OperationCanceled);
+        assert!(is_cancellation(&err));
+        assert!(!is_cancellation(&anyhow::anyhow!("zellij failed")));
+    }
+
+    #[test]
+    #[serial]
+    fn run_popup_refuses_outside_zellij() {
+        let previous = std::env::var_os("ZELLIJ_SESSION_NAME");
         unsafe { std::env::remove_var("ZELLIJ_SESSION_NAME") };
-        assert!(!is_inside_zellij());
+        let result = run_popup(&Config::default());
+        restore_var("ZELLIJ_SESSION_NAME", previous);
+        assert!(result.is_err());
     }
 }
@@ -0,0 +1,262 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::git;
+
+/// How `CreateWithDir` derives a session name from the chosen directory.
+///
+/// Written in the config as one of the keywords below, or as a template such
+/// as `"{repo}-{branch}"` using the placeholders `{basename}`, `{parent}`,
+/// `{repo}` and `{branch}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum NamingStrategy {
+    /// `backend`
+    #[default]
+    Basename,
+    /// `work-backend`
+    ParentBasename,
+    /// Name of the git repository containing the directory
+    Repo,
+    /// `backend-feature-login`
+    RepoBranch,
+    Template(String),
+}
+
+/// Placeholders a naming template can use.
+const PLACEHOLDERS: &[&str] = &["{basename}", "{parent}", "{repo}", "{branch}"];
+
+impl TryFrom<String> for NamingStrategy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "basename" => Ok(NamingStrategy::Basename),
+            "parent-basename" => Ok(NamingStrategy::ParentBasename),
+            "repo" => Ok(NamingStrategy::Repo),
+            "repo-branch" => Ok(NamingStrategy::RepoBranch),
+            // Without a placeholder every session would get the same name,
+            // so this is most likely a misspelled keyword.
+            _ if PLACEHOLDERS.iter().any(|p| value.contains(p)) => {
+                Ok(NamingStrategy::Template(value))
+            }
+            _ => Err(format!(
+                "unknown naming strategy '{value}': use basename, parent-basename, repo, \
+                 repo-branch or a template with {}",
+                PLACEHOLDERS.join(", ")
+            )),
+        }
+    }
+}
+
+impl From<NamingStrategy> for String {
+    fn from(value: NamingStrategy) -> Self {
+        match value {
+            NamingStrategy::Basename => "basename".to_string(),
+            NamingStrategy::ParentBasename => "parent-basename".to_string(),
+            NamingStrategy::Repo => "repo".to_string(),
+            NamingStrategy::RepoBranch => "repo-branch".to_string(),
+            NamingStrategy::Template(template) => template,
+        }
+    }
+}
+
+impl NamingStrategy {
+    fn template(&self) -> Cow<'_, str> {
+        match self {
+            NamingStrategy::Basename => "{basename}".into(),
+            NamingStrategy::ParentBasename => "{parent}-{basename}".into(),
+            NamingStrategy::Repo => "{repo}".into(),
+            NamingStrategy::RepoBranch => "{repo}-{branch}".into(),
+            NamingStrategy::Template(template) => template.as_str().into(),
+        }
+    }
+}
+
+/// Values substituted into a naming template.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Placeholders {
+    pub basename: String,
+    pub parent: String,
+    pub repo: String,
+    pub branch: String,
+}
+
+fn file_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().to_string(),
+        // Only the filesystem root has no file name.
+        None => "root".to_string(),
+    }
+}
+
+impl Placeholders {
+    /// Collects placeholder values for `dir`, only asking git when the
+    /// template needs it. Outside a repository `{repo}` falls back to the
+    /// basename and `{branch}` is empty.
+    fn for_dir(dir: &Path, template: &str) -> Self {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let basename = file_name(&dir);
+        let parent = dir.parent().map(file_name).unwrap_or_default();
+
+        let repo = if template.contains("{repo}") {
+            git::toplevel(&dir)
+                .map(|root| file_name(&root))
+                .unwrap_or_else(|| basename.clone())
+        } else {
+            String::new()
+        };
+        let branch = if template.contains("{branch}") {
+            git::current_branch(&dir).unwrap_or_default()
+        } else {
+            String::new()
+        };
+
+        Self {
+            basename,
+            parent,
+            repo,
+            branch,
+        }
+    }
+}
+
+/// Replaces anything Zellij rejects in a session name with `-`, collapsing
+/// runs and trimming them from both ends.
+pub fn sanitize(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_whitespace() || c.is_control() || c == '/' {
+            if !out.ends_with('-') {
+                out.push('-');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out.trim_matches('-').to_string()
+}
+
+pub fn render(template: &str, placeholders: &Placeholders) -> String {
+    let name = template
+        .replace("{basename}", &placeholders.basename)
+        .replace("{parent}", &placeholders.parent)
+        .replace("{repo}", &placeholders.repo)
+        .replace("{branch}", &placeholders.branch);
+    sanitize(&name)
+}
+
+/// Derives a session name for `dir`. Falls back to the basename when the
+/// template renders to nothing.
+pub fn derive_name(strategy: &NamingStrategy, dir: &Path) -> String {
+    let template = strategy.template();
+    let placeholders = Placeholders::for_dir(dir, &template);
+    let name = render(&template, &placeholders);
+    if name.is_empty() {
+        sanitize(&placeholders.basename)
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders() -> Placeholders {
+        Placeholders {
+            basename: "backend".to_string(),
+            parent: "work".to_string(),
+            repo: "shop".to_string(),
+            branch: "feature/login".to_string(),
+        }
+    }
+
+    #[test]
+    fn strategy_parses_keywords_and_templates() {
+        assert_eq!(
+            NamingStrategy::try_from("repo-branch".to_string()),
+            Ok(NamingStrategy::RepoBranch)
+        );
+        assert_eq!(
+            NamingStrategy::try_from("{repo}@{branch}".to_string()),
+            Ok(NamingStrategy::Template("{repo}@{branch}".to_string()))
+        );
+        assert_eq!(
+            String::from(NamingStrategy::ParentBasename),
+            "parent-basename"
+        );
+    }
+
+    #[test]
+    fn strategy_rejects_unknown_keywords() {
+        let err = NamingStrategy::try_from("repo_branch".to_string()).unwrap_err();
+        assert!(err.contains("repo_branch"));
+        assert!(err.contains("parent-basename"));
+        assert!(NamingStrategy::try_from("oncall".to_string()).is_err());
+    }
+
+    #[test]
+    fn render_builtin_strategies() {
+        let p = placeholders();
+        assert_eq!(render(&NamingStrategy::Basename.template(), &p), "backend");
+        assert_eq!(
+            render(&NamingStrategy::ParentBasename.template(), &p),
+            "work-backend"
+        );
+        assert_eq!(render(&NamingStrategy::Repo.template(), &p), "shop");
+        assert_eq!(
+            render(&NamingStrategy::RepoBranch.template(), &p),
+            "shop-feature-login"
+        );
+    }
+
+    #[test]
+    fn render_trims_separator_left_by_empty_placeholder() {
+        let p = Placeholders {
+            branch: String::new(),
+            ..placeholders()
+        };
+        assert_eq!(render("{repo}-{branch}", &p), "shop");
+    }
+
+    #[test]
+    fn sanitize_replaces_rejected_chars() {
+        assert_eq!(sanitize("my project/v2"), "my-project-v2");
+        assert_eq!(sanitize("  spaced  "), "spaced");
+    }
+
+    #[test]
+    fn derive_name_uses_root_for_filesystem_root() {
+        assert_eq!(
+            derive_name(&NamingStrategy::Basename, Path::new("/")),
+            "root"
+        );
+    }
+
+    #[test]
+    fn derive_name_resolves_dot_dot() {
+        let tmp = tempfile::Builder::new()
+            .prefix("zism-naming-")
+            .tempdir()
+            .unwrap();
+        let dir = tmp.path().join("child");
+        std::fs::create_dir_all(&dir).unwrap();
+        let name = derive_name(&NamingStrategy::Basename, &dir.join(".."));
+        assert_eq!(name, file_name(tmp.path()));
+    }
+
+    #[test]
+    fn derive_name_repo_falls_back_to_basename_outside_git() {
+        let tmp = tempfile::Builder::new()
+            .prefix("zism-naming-")
+            .tempdir()
+            .unwrap();
+        assert_eq!(
+            derive_name(&NamingStrategy::Repo, tmp.path()),
+            file_name(tmp.path())
+        );
+    }
+}
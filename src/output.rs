@@ -0,0 +1,212 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::registry::{self, Entry};
+use crate::zellij::{self, Session};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// One session name per line
+    #[default]
+    Plain,
+    /// JSON array of session objects
+    Json,
+    /// Tab-separated `name, state, age_secs, directory` rows
+    Tsv,
+}
+
+/// Stable, machine-readable view of a session. Field names are part of the
+/// `zism list --format json` contract, so only add fields, never rename them.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct SessionEntry {
+    pub name: String,
+    pub state: &'static str,
+    pub exited: bool,
+    pub current: bool,
+    pub age_secs: Option<u64>,
+    pub directory: Option<PathBuf>,
+    pub layout: Option<String>,
+    pub created_at: Option<u64>,
+    pub last_attached_at: Option<u64>,
+    pub tags: Vec<String>,
+}
+
+impl SessionEntry {
+    /// Combines what Zellij reports with what the registry recorded.
+    pub fn new(session: &Session, entry: Option<&Entry>) -> Self {
+        let entry = entry.cloned().unwrap_or_default();
+        Self {
+            name: session.name.clone(),
+            state: session.state(),
+            exited: session.exited,
+            current: session.current,
+            age_secs: session.age.map(|age| age.as_secs()),
+            directory: entry.directory,
+            layout: entry.layout,
+            created_at: entry.created_at,
+            last_attached_at: entry.last_attached_at,
+            tags: entry.tags,
+        }
+    }
+}
+
+fn format_timestamp(timestamp: Option<u64>, now: u64) -> String {
+    match timestamp {
+        Some(ts) => format!(
+            "{} ago",
+            zellij::format_age(std::time::Duration::from_secs(now.saturating_sub(ts)))
+        ),
+        None => "-".to_string(),
+    }
+}
+
+/// Human-readable report for `zism info`.
+pub fn render_info(name: &str, session: Option<&Session>, entry: Option<&Entry>) -> String {
+    let now = registry::now();
+    let entry = entry.cloned().unwrap_or_default();
+    let rows = [
+        ("name", name.to_string()),
+        ("state", session.map_or("gone", Session::state).to_string()),
+        (
+            "directory",
+            entry
+                .directory
+                .map_or("-".to_string(), |d| d.display().to_string()),
+        ),
+        ("layout", entry.layout.unwrap_or_else(|| "-".to_string())),
+        ("created", format_timestamp(entry.created_at, now)),
+        (
+            "last attached",
+            format_timestamp(entry.last_attached_at, now),
+        ),
+        (
+            "tags",
+            if entry.tags.is_empty() {
+                "-".to_string()
+            } else {
+                entry.tags.join(", ")
+            },
+        ),
+    ];
+    rows.iter()
+        .map(|(key, value)| format!("{:<14} {value}\n", format!("{key}:")))
+        .collect()
+}
+
+pub fn render_sessions(entries: &[SessionEntry], format: Format) -> Result<String> {
+    let output = match format {
+        Format::Plain => entries.iter().map(|e| format!("{}\n", e.name)).collect(),
+        Format::Json => format!("{}\n", serde_json::to_string_pretty(entries)?),
+        Format::Tsv => entries
+            .iter()
+            .map(|e| {
+                format!(
+                    "{}\t{}\t{}\t{}\n",
+                    e.name,
+                    e.state,
+                    e.age_secs.map(|s| s.to_string()).unwrap_or_default(),
+                    e.directory
+                        .as_ref()
+                        .map(|d| d.display().to_string())
+                        .unwrap_or_default()
+                )
+            })
+            .collect(),
+    };
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn entries() -> Vec<SessionEntry> {
+        let sessions = [
+            Session {
+                name: "api".to_string(),
+                age: Some(Duration::from_secs(90)),
+                exited: false,
+                current: false,
+            },
+            Session {
+                name: "old".to_string(),
+                age: None,
+                exited: true,
+                current: false,
+            },
+        ];
+        let entry = Entry {
+            directory: Some(PathBuf::from("/srv/api")),
+            layout: Some("compact".to_string()),
+            created_at: Some(100),
+            last_attached_at: Some(200),
+            attach_count: 2,
+            tags: vec!["work".to_string()],
+        };
+        vec![
+            SessionEntry::new(&sessions[0], Some(&entry)),
+            SessionEntry::new(&sessions[1], None),
+        ]
+    }
+
+    #[test]
+    fn render_plain_prints_names() {
+        let output = render_sessions(&entries(), Format::Plain).unwrap();
+        assert_eq!(output, "api\nold\n");
+    }
+
+    #[test]
+    fn render_tsv_prints_columns() {
+        let output = render_sessions(&entries(), Format::Tsv).unwrap();
+        assert_eq!(output, "api\trunning\t90\t/srv/api\nold\texited\t\t\n");
+    }
+
+    #[test]
+    fn render_json_uses_stable_schema() {
+        let output = render_sessions(&entries(), Format::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(
+            value[0],
+            serde_json::json!({
+                "name": "api",
+                "state": "running",
+                "exited": false,
+                "current": false,
+                "age_secs": 90,
+                "directory": "/srv/api",
+                "layout": "compact",
+                "created_at": 100,
+                "last_attached_at": 200,
+                "tags": ["work"],
+            })
+        );
+        assert_eq!(value[1]["age_secs"], serde_json::Value::Null);
+        assert_eq!(value[1]["directory"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn render_info_reports_unknown_fields_as_dash() {
+        let output = render_info("ghost", None, None);
+        assert_eq!(
+            output,
+            "name:          ghost\n\
+             state:         gone\n\
+             directory:     -\n\
+             layout:        -\n\
+             created:       -\n\
+             last attached: -\n\
+             tags:          -\n"
+        );
+    }
+
+    #[test]
+    fn render_json_prints_empty_array_without_sessions() {
+        let output = render_sessions(&[], Format::Json).unwrap();
+        assert_eq!(output, "[]\n");
+    }
+}
@@ -0,0 +1,267 @@
+use std::ffi::{CStr, CString, OsStr};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::xdg;
+
+/// Home directory of `user` from the system user database, so LDAP and
+/// other NSS sources work as well as `/etc/passwd`.
+fn user_home(user: &str) -> Option<PathBuf> {
+    let name = CString::new(user).ok()?;
+    let mut buf = vec![0 as libc::c_char; 1024];
+    loop {
+        let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let rc = unsafe {
+            libc::getpwnam_r(
+                name.as_ptr(),
+                &mut entry,
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut result,
+            )
+        };
+        if rc == libc::ERANGE {
+            buf.resize(buf.len() * 2, 0);
+            continue;
+        }
+        if rc != 0 || result.is_null() || entry.pw_dir.is_null() {
+            return None;
+        }
+        // `pw_dir` points into `buf`, which is still alive here.
+        let dir = unsafe { CStr::from_ptr(entry.pw_dir) };
+        return Some(PathBuf::from(OsStr::from_bytes(dir.to_bytes())));
+    }
+}
+
+/// Expands a leading `~` or `~user`. Unknown users are left as typed.
+pub fn expand_tilde(path: &Path) -> PathBuf {
+    if let Ok(rest) = path.strip_prefix("~") {
+        return xdg::home_dir().join(rest);
+    }
+    let text = path.to_string_lossy();
+    let Some(user_part) = text.strip_prefix('~') else {
+        return path.to_path_buf();
+    };
+    let (user, rest) = user_part.split_once('/').unwrap_or((user_part, ""));
+    match user_home(user) {
+        Some(home) => home.join(rest),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Replaces `$VAR` and `${VAR}` with their values. Unset variables are left
+/// as typed so the mistake stays visible, and `\$` stands for a literal `$`.
+pub fn expand_vars(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('$') {
+        if let Some(before) = rest[..start].strip_suffix('\\') {
+            out.push_str(before);
+            out.push('$');
+            rest = &rest[start + 1..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        let value = (!name.is_empty())
+            .then(|| std::env::var(name).ok())
+            .flatten();
+        match value {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..start + 1 + len]),
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Resolves typed input to a path: variables and `~` are expanded, and
+/// relative paths (including `..`) start from `base`.
+pub fn resolve(input: &str, base: &Path) -> PathBuf {
+    let expanded = expand_tilde(Path::new(&expand_vars(input)));
+    base.join(expanded)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DirStatus {
+    /// An existing directory a session can start in
+    Usable,
+    /// Nothing exists at the path yet
+    Missing,
+}
+
+/// Checks that `path` is a directory a session can start in, or that it
+/// does not exist yet. Fails for files and inaccessible directories.
+pub fn check_dir(path: &Path) -> Result<DirStatus> {
+    let metadata = match std::fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(DirStatus::Missing),
+        Err(err) => return Err(err).with_context(|| format!("Cannot access {}", path.display())),
+    };
+    if !metadata.is_dir() {
+        bail!("{} is not a directory", path.display());
+    }
+    // A session only needs to enter the directory, so test search permission
+    // rather than listing it.
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .with_context(|| format!("Invalid path {}", path.display()))?;
+    if unsafe { libc::access(c_path.as_ptr(), libc::X_OK) } != 0 {
+        return Err(std::io::Error::last_os_error())
+            .with_context(|| format!("Cannot access {}", path.display()));
+    }
+    Ok(DirStatus::Usable)
+}
+
+/// Creates `path` and any missing parents, like `mkdir -p`.
+pub fn create_dir(path: &Path) -> Result<()> {
+    std::fs::create_dir_all(path).with_context(|| format!("Failed to create {}", path.display()))
+}
+
+/// Canonical form of `path`, which must be an existing directory.
+pub fn existing_dir(path: &Path) -> Result<PathBuf> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("{} does not exist", path.display()))?;
+    if !canonical.is_dir() {
+        bail!("{} is not a directory", path.display());
+    }
+    Ok(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+
+    #[test]
+    fn expand_tilde_expands_other_users() {
+        let root = user_home("root").expect("root is in the user database");
+        assert!(root.is_absolute());
+        assert_eq!(expand_tilde(Path::new("~root/src")), root.join("src"));
+    }
+
+    #[test]
+    fn expand_tilde_expands_own_home() {
+        assert_eq!(
+            expand_tilde(Path::new("~/src")),
+            xdg::home_dir().join("src")
+        );
+        assert_eq!(expand_tilde(Path::new("~")), xdg::home_dir());
+        assert_eq!(expand_tilde(Path::new("/srv")), PathBuf::from("/srv"));
+    }
+
+    #[test]
+    fn expand_tilde_leaves_unknown_users() {
+        let path = Path::new("~no-such-zism-user/src");
+        assert_eq!(expand_tilde(path), path);
+    }
+
+    #[test]
+    #[serial]
+    fn expand_vars_replaces_set_variables() {
+        unsafe { std::env::set_var("ZISM_TEST_WORK", "/srv/work") };
+        assert_eq!(expand_vars("$ZISM_TEST_WORK/api"), "/srv/work/api");
+        assert_eq!(expand_vars("${ZISM_TEST_WORK}api"), "/srv/workapi");
+        unsafe { std::env::remove_var("ZISM_TEST_WORK") };
+    }
+
+    #[test]
+    #[serial]
+    fn expand_vars_keeps_unset_variables_and_lone_dollars() {
+        unsafe { std::env::remove_var("ZISM_TEST_UNSET") };
+        assert_eq!(expand_vars("$ZISM_TEST_UNSET/x"), "$ZISM_TEST_UNSET/x");
+        assert_eq!(expand_vars("a$/b${"), "a$/b${");
+    }
+
+    #[test]
+    #[serial]
+    fn expand_vars_keeps_escaped_dollars() {
+        unsafe { std::env::set_var("ZISM_TEST_WORK", "/srv/work") };
+        assert_eq!(expand_vars(r"\$ZISM_TEST_WORK/x"), "$ZISM_TEST_WORK/x");
+        assert_eq!(
+            expand_vars(r"price\$5/$ZISM_TEST_WORK"),
+            "price$5//srv/work"
+        );
+        unsafe { std::env::remove_var("ZISM_TEST_WORK") };
+    }
+
+    #[test]
+    fn resolve_handles_relative_absolute_and_home_paths() {
+        let base = Path::new("/base");
+        assert_eq!(resolve("src", base), PathBuf::from("/base/src"));
+        assert_eq!(resolve("../x", base), PathBuf::from("/base/../x"));
+        assert_eq!(resolve("/srv/app", base), PathBuf::from("/srv/app"));
+        assert_eq!(resolve("~/src", base), xdg::home_dir().join("src"));
+        assert_eq!(resolve("", base), PathBuf::from("/base/"));
+    }
+
+    #[test]
+    fn check_dir_reports_usable_and_missing_directories() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::write(dir.join("file"), "").unwrap();
+        assert_eq!(check_dir(&dir).unwrap(), DirStatus::Usable);
+        assert_eq!(
+            check_dir(&dir.join("new/nested")).unwrap(),
+            DirStatus::Missing
+        );
+        assert!(check_dir(&dir.join("file")).is_err());
+        assert!(check_dir(&dir.join("file/sub")).is_err());
+    }
+
+    #[test]
+    fn check_dir_rejects_unreadable_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Root passes every permission check.
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("locked");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o000)).unwrap();
+        let result = check_dir(&dir);
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn create_dir_creates_parents() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        create_dir(&dir.join("a/b")).unwrap();
+        assert!(dir.join("a/b").is_dir());
+    }
+
+    #[test]
+    fn existing_dir_canonicalizes_and_rejects_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().to_path_buf();
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("file"), "").unwrap();
+        assert_eq!(
+            existing_dir(&dir.join("sub/..")).unwrap(),
+            dir.canonicalize().unwrap()
+        );
+        assert!(existing_dir(&dir.join("file")).is_err());
+        assert!(existing_dir(&dir.join("missing")).is_err());
+    }
+}
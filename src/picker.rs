@@ -0,0 +1,662 @@
+//! The list prompt behind every picker: it looks like inquire's `Select` and
+//! `MultiSelect`, ranks options with `fuzzy`, and can bind extra keys.
+
+use std::collections::BTreeSet;
+use std::io::{IsTerminal, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Stylize};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, queue};
+use inquire::InquireError;
+use inquire::error::InquireResult;
+use inquire::ui::{Attributes, RenderConfig, StyleSheet, Styled};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::fuzzy;
+
+const SELECT_HELP: &str = "↑↓ to move, enter to select, type to filter";
+const MULTI_SELECT_HELP: &str =
+    "↑↓ to move, space to toggle, a or → to select all, ← to clear, type to filter";
+
+/// What the user did with a picker.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome<K> {
+    /// Indices of the chosen options: the highlighted one, or the checked
+    /// ones of a multi-select in their original order
+    Picked(Vec<usize>),
+    /// A key registered with `with_key` was pressed
+    Key(K),
+    /// The prompt was left with ESC
+    Skipped,
+}
+
+/// Result of handling one key press.
+#[derive(Debug, PartialEq, Eq)]
+enum Step<K> {
+    Continue,
+    Done(Outcome<K>),
+    Interrupted,
+}
+
+pub struct Picker<'a, K> {
+    message: &'a str,
+    labels: Vec<String>,
+    /// Text shown for each option once picked, when not its label
+    answers: Option<Vec<String>>,
+    render_config: RenderConfig<'static>,
+    page_size: usize,
+    help: Option<String>,
+    multi: bool,
+    /// The last option stays listed after the matches, whatever the filter
+    pinned_last: bool,
+    keys: Vec<(KeyCode, K)>,
+    filter: String,
+    /// Indices into `labels` of the options matching `filter`, best first
+    visible: Vec<usize>,
+    /// Position of the highlighted option in `visible`
+    cursor: usize,
+    /// Position in `visible` of the first option on screen
+    offset: usize,
+    checked: BTreeSet<usize>,
+}
+
+impl<'a, K: Copy> Picker<'a, K> {
+    pub fn new(message: &'a str, labels: Vec<String>) -> Self {
+        let visible = (0..labels.len()).collect();
+        Self {
+            message,
+            labels,
+            answers: None,
+            render_config: RenderConfig::default(),
+            page_size: 7,
+            help: None,
+            multi: false,
+            pinned_last: false,
+            keys: Vec::new(),
+            filter: String::new(),
+            visible,
+            cursor: 0,
+            offset: 0,
+            checked: BTreeSet::new(),
+        }
+    }
+
+    pub fn with_render_config(mut self, render_config: RenderConfig<'static>) -> Self {
+        self.render_config = render_config;
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    pub fn with_help_message(mut self, help: &str) -> Self {
+        self.set_help_message(help);
+        self
+    }
+
+    pub fn set_help_message(&mut self, help: &str) {
+        self.help = Some(help.to_string());
+    }
+
+    /// Shows `answers[i]` instead of the label of option `i` once picked.
+    pub fn with_answers(mut self, answers: Vec<String>) -> Self {
+        self.answers = Some(answers);
+        self
+    }
+
+    /// Highlights option `index` when the prompt opens. Call it after
+    /// `with_page_size`, so the option is on the first page shown.
+    pub fn with_starting_cursor(mut self, index: usize) -> Self {
+        if let Some(position) = self.visible.iter().position(|&i| i == index) {
+            self.move_cursor(position as isize);
+        }
+        self
+    }
+
+    /// Keeps the last option, such as a way out of the picker, listed below
+    /// the matching ones however the filter changes.
+    pub fn with_pinned_last(mut self) -> Self {
+        self.pinned_last = true;
+        self
+    }
+
+    /// Lets the user check any number of options with SPACE.
+    pub fn with_multi_select(mut self, checked: bool) -> Self {
+        self.multi = true;
+        if checked {
+            self.checked = (0..self.labels.len()).collect();
+        }
+        self
+    }
+
+    /// Ends the prompt with `Outcome::Key(value)` when `key` is pressed.
+    /// Letters only count while nothing is typed, so they still filter.
+    pub fn with_key(mut self, key: KeyCode, value: K) -> Self {
+        self.keys.push((key, value));
+        self
+    }
+
+    /// Replaces the options, for example after re-sorting them, keeping the
+    /// filter text. Checked options are cleared.
+    pub fn set_labels(&mut self, labels: Vec<String>) {
+        self.labels = labels;
+        self.checked.clear();
+        self.refilter();
+    }
+
+    fn refilter(&mut self) {
+        let ranked = match self.pinned_last {
+            true => self.labels.len().saturating_sub(1),
+            false => self.labels.len(),
+        };
+        let mut scored: Vec<_> = self.labels[..ranked]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, label)| fuzzy::fuzzy_match(&self.filter, label).map(|m| (i, m.score)))
+            .collect();
+        fuzzy::sorter(&mut scored);
+        self.visible = scored.into_iter().map(|(i, _)| i).collect();
+        self.visible.extend(ranked..self.labels.len());
+        self.cursor = 0;
+        self.offset = 0;
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let last = self.visible.len() - 1;
+        self.cursor = self.cursor.saturating_add_signed(delta).min(last);
+        if self.cursor < self.offset {
+            self.offset = self.cursor;
+        } else if self.cursor >= self.offset + self.page_size {
+            self.offset = self.cursor + 1 - self.page_size;
+        }
+    }
+
+    fn bound_key(&self, code: KeyCode) -> Option<K> {
+        if matches!(code, KeyCode::Char(_)) && !self.filter.is_empty() {
+            return None;
+        }
+        self.keys
+            .iter()
+            .find(|(key, _)| *key == code)
+            .map(|(_, value)| *value)
+    }
+
+    fn handle(&mut self, key: KeyEvent) -> Step<K> {
+        let page = self.page_size as isize;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c') if ctrl => return Step::Interrupted,
+            KeyCode::Esc => return Step::Done(Outcome::Skipped),
+            KeyCode::Enter => {
+                let picked = if self.multi {
+                    self.checked.iter().copied().collect()
+                } else {
+                    match self.visible.get(self.cursor) {
+                        Some(&index) => vec![index],
+                        None => return Step::Continue,
+                    }
+                };
+                return Step::Done(Outcome::Picked(picked));
+            }
+            code if self.bound_key(code).is_some() && !ctrl => {
+                return Step::Done(Outcome::Key(self.bound_key(code).unwrap()));
+            }
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Char('p') if ctrl => self.move_cursor(-1),
+            KeyCode::Down => self.move_cursor(1),
+            KeyCode::Char('n') if ctrl => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-page),
+            KeyCode::PageDown => self.move_cursor(page),
+            KeyCode::Home => self.move_cursor(isize::MIN),
+            KeyCode::End => self.move_cursor(isize::MAX),
+            KeyCode::Char(' ') if self.multi => {
+                if let Some(&index) = self.visible.get(self.cursor)
+                    && !self.checked.remove(&index)
+                {
+                    self.checked.insert(index);
+                }
+            }
+            KeyCode::Char('a') if self.multi && self.filter.is_empty() => {
+                self.checked.extend(self.visible.iter().copied());
+            }
+            KeyCode::Right if self.multi => self.checked.extend(self.visible.iter().copied()),
+            KeyCode::Left if self.multi => self.checked.clear(),
+            KeyCode::Backspace if self.filter.pop().is_some() => self.refilter(),
+            KeyCode::Char(c) if !ctrl => {
+                self.filter.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+        Step::Continue
+    }
+
+    /// Shows the prompt until the user picks, skips or presses a bound key.
+    pub fn prompt(&mut self) -> InquireResult<Outcome<K>> {
+        if !std::io::stdin().is_terminal() {
+            return Err(InquireError::NotTTY);
+        }
+        let mut out = std::io::stderr();
+        let _raw = RawMode::enable()?;
+        loop {
+            self.draw(&mut out)?;
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                _ => continue,
+            };
+            match self.handle(key) {
+                Step::Continue => {}
+                Step::Done(outcome) => {
+                    self.finish(&mut out, &outcome)?;
+                    return Ok(outcome);
+                }
+                Step::Interrupted => {
+                    self.finish(&mut out, &Outcome::Skipped)?;
+                    return Err(InquireError::OperationInterrupted);
+                }
+            }
+        }
+    }
+
+    /// The prefix and message, cut to `width` columns, and a space.
+    fn prompt_line(&self, prefix: Styled<&str>, width: usize) -> String {
+        let message = head(
+            self.message,
+            width.saturating_sub(prefix.content.width() + 2),
+        );
+        format!(
+            "{} {} ",
+            styled(prefix.content, prefix.style),
+            styled(message, self.render_config.prompt)
+        )
+    }
+
+    /// The filter text that fits after the prompt in `width` columns: its
+    /// end, where the user is typing.
+    fn shown_filter(&self, width: usize) -> &str {
+        let prompt = self.render_config.prompt_prefix.content.width() + self.message.width() + 2;
+        tail(&self.filter, width.saturating_sub(prompt))
+    }
+
+    /// The lines of the current frame, each at most `width` columns wide.
+    fn frame(&self, width: usize) -> Vec<String> {
+        let config = &self.render_config;
+        let mut lines = vec![format!(
+            "{}{}",
+            self.prompt_line(config.prompt_prefix, width),
+            styled(self.shown_filter(width), config.text_input)
+        )];
+        let end = (self.offset + self.page_size).min(self.visible.len());
+        for (row, &index) in self.visible[self.offset..end].iter().enumerate() {
+            let position = self.offset + row;
+            let highlighted = position == self.cursor;
+            let prefix = if highlighted {
+                config.highlighted_option_prefix
+            } else if row == 0 && self.offset > 0 {
+                config.scroll_up_prefix
+            } else if position + 1 == end && end < self.visible.len() {
+                config.scroll_down_prefix
+            } else {
+                config.unhighlighted_option_prefix
+            };
+            let style = match config.selected_option {
+                Some(selected) if highlighted => selected,
+                _ => config.option,
+            };
+            let mut line = format!("{} ", styled(prefix.content, prefix.style));
+            let mut used = prefix.content.width() + 1;
+            if self.multi {
+                let mut checkbox = if self.checked.contains(&index) {
+                    config.selected_checkbox
+                } else {
+                    config.unselected_checkbox
+                };
+                if highlighted && let Some(selected) = config.selected_option {
+                    checkbox.style = selected;
+                }
+                line.push_str(&styled(checkbox.content, checkbox.style));
+                line.push(' ');
+                used += checkbox.content.width() + 1;
+            }
+            let label = head(&self.labels[index], width.saturating_sub(used));
+            let label = fuzzy::highlight(&self.filter, label);
+            line.push_str(&styled(&label, style));
+            lines.push(line);
+        }
+        let default_help = if self.multi {
+            MULTI_SELECT_HELP
+        } else {
+            SELECT_HELP
+        };
+        let help = head(
+            self.help.as_deref().unwrap_or(default_help),
+            width.saturating_sub(2),
+        );
+        lines.push(styled(&format!("[{help}]"), config.help_message));
+        lines
+    }
+
+    /// Redraws the frame from the prompt line, where the cursor rests. No
+    /// line is wider than the terminal, so each takes exactly one row.
+    fn draw(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let width = match terminal::size() {
+            Ok((width, _)) if width > 0 => usize::from(width),
+            _ => 80,
+        };
+        let width = width.saturating_sub(1);
+        let lines = self.frame(width);
+        queue!(
+            out,
+            cursor::MoveToColumn(0),
+            terminal::Clear(ClearType::FromCursorDown)
+        )?;
+        write!(out, "{}", lines.join("\r\n"))?;
+        if lines.len() > 1 {
+            queue!(out, cursor::MoveUp((lines.len() - 1) as u16))?;
+        }
+        let prefix = self.render_config.prompt_prefix.content;
+        let column = [prefix, self.message, self.shown_filter(width)]
+            .iter()
+            .map(|part| part.width() + 1)
+            .sum::<usize>()
+            - 1;
+        queue!(out, cursor::MoveToColumn(column.min(width) as u16))?;
+        out.flush()
+    }
+
+    /// Replaces the frame with the answered (or canceled) prompt line.
+    fn finish(&self, out: &mut impl Write, outcome: &Outcome<K>) -> std::io::Result<()> {
+        let config = &self.render_config;
+        queue!(
+            out,
+            cursor::MoveToColumn(0),
+            terminal::Clear(ClearType::FromCursorDown)
+        )?;
+        let line = match outcome {
+            Outcome::Picked(indices) => {
+                let answer = indices
+                    .iter()
+                    .map(|&i| self.answers.as_ref().unwrap_or(&self.labels)[i].as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                format!(
+                    "{}{}",
+                    self.prompt_line(config.answered_prompt_prefix, usize::MAX),
+                    styled(&answer, config.answer)
+                )
+            }
+            Outcome::Skipped => format!(
+                "{}{}",
+                self.prompt_line(config.prompt_prefix, usize::MAX),
+                styled(
+                    config.canceled_prompt_indicator.content,
+                    config.canceled_prompt_indicator.style
+                )
+            ),
+            // The caller shows the prompt again right away.
+            Outcome::Key(_) => return out.flush(),
+        };
+        write!(out, "{line}\r\n")?;
+        out.flush()
+    }
+}
+
+/// The start of `text` that fits in `width` columns.
+fn head(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in text.char_indices() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// The end of `text` that fits in `width` columns.
+fn tail(text: &str, width: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in text.char_indices().rev() {
+        used += c.width().unwrap_or(0);
+        if used > width {
+            return &text[i + c.len_utf8()..];
+        }
+    }
+    text
+}
+
+/// `text` with inquire's style sheet applied as ANSI escapes.
+fn styled(text: &str, sheet: StyleSheet) -> String {
+    let mut content = text.stylize();
+    if let Some(fg) = sheet.fg {
+        content = content.with(fg.into());
+    }
+    if let Some(bg) = sheet.bg {
+        content = content.on(bg.into());
+    }
+    if sheet.att.contains(Attributes::BOLD) {
+        content = content.attribute(Attribute::Bold);
+    }
+    if sheet.att.contains(Attributes::ITALIC) {
+        content = content.attribute(Attribute::Italic);
+    }
+    content.to_string()
+}
+
+/// Raw terminal mode for as long as the value lives.
+struct RawMode;
+
+impl RawMode {
+    fn enable() -> std::io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn picker() -> Picker<'static, char> {
+        Picker::new(
+            "Pick:",
+            ["api", "web", "worker", "docs"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        )
+        .with_page_size(2)
+    }
+
+    fn press(picker: &mut Picker<'_, char>, code: KeyCode) -> Step<char> {
+        picker.handle(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(picker: &mut Picker<'_, char>, text: &str) {
+        for c in text.chars() {
+            press(picker, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn enter_picks_highlighted_option() {
+        let mut picker = picker();
+        press(&mut picker, KeyCode::Down);
+        assert_eq!(
+            press(&mut picker, KeyCode::Enter),
+            Step::Done(Outcome::Picked(vec![1]))
+        );
+    }
+
+    #[test]
+    fn typing_filters_and_ranks_options() {
+        let mut picker = picker();
+        type_text(&mut picker, "wr");
+        assert_eq!(picker.visible, vec![2]);
+        press(&mut picker, KeyCode::Backspace);
+        assert_eq!(picker.visible, vec![1, 2]);
+        type_text(&mut picker, "zz");
+        assert_eq!(press(&mut picker, KeyCode::Enter), Step::Continue);
+    }
+
+    #[test]
+    fn every_letter_goes_to_the_filter() {
+        let mut picker = picker();
+        type_text(&mut picker, "jk");
+        assert_eq!(picker.filter, "jk");
+        assert_eq!(picker.cursor, 0);
+    }
+
+    #[test]
+    fn starting_cursor_highlights_the_option() {
+        let picker = picker().with_starting_cursor(3);
+        assert_eq!((picker.cursor, picker.offset), (3, 2));
+    }
+
+    #[test]
+    fn pinned_last_option_stays_below_matches() {
+        let mut picker = picker().with_pinned_last();
+        type_text(&mut picker, "w");
+        assert_eq!(picker.visible, vec![1, 2, 3]);
+        type_text(&mut picker, "zz");
+        assert_eq!(picker.visible, vec![3]);
+    }
+
+    #[test]
+    fn cursor_scrolls_the_page() {
+        let mut picker = picker();
+        press(&mut picker, KeyCode::End);
+        assert_eq!((picker.cursor, picker.offset), (3, 2));
+        press(&mut picker, KeyCode::Up);
+        press(&mut picker, KeyCode::Up);
+        assert_eq!((picker.cursor, picker.offset), (1, 1));
+        press(&mut picker, KeyCode::PageUp);
+        assert_eq!((picker.cursor, picker.offset), (0, 0));
+    }
+
+    #[test]
+    fn bound_letters_only_apply_before_typing() {
+        let mut picker = picker().with_key(KeyCode::Char('s'), 's');
+        assert_eq!(
+            press(&mut picker, KeyCode::Char('s')),
+            Step::Done(Outcome::Key('s'))
+        );
+        type_text(&mut picker, "w");
+        assert_eq!(press(&mut picker, KeyCode::Char('s')), Step::Continue);
+        assert_eq!(picker.filter, "ws");
+    }
+
+    #[test]
+    fn bound_keys_apply_while_filtering() {
+        let mut picker = picker().with_key(KeyCode::Tab, 't');
+        type_text(&mut picker, "w");
+        assert_eq!(
+            press(&mut picker, KeyCode::Tab),
+            Step::Done(Outcome::Key('t'))
+        );
+    }
+
+    #[test]
+    fn multi_select_toggles_and_selects_all() {
+        let mut picker = picker().with_multi_select(false);
+        press(&mut picker, KeyCode::Char(' '));
+        press(&mut picker, KeyCode::Down);
+        press(&mut picker, KeyCode::Char(' '));
+        press(&mut picker, KeyCode::Up);
+        press(&mut picker, KeyCode::Char(' '));
+        assert_eq!(
+            press(&mut picker, KeyCode::Enter),
+            Step::Done(Outcome::Picked(vec![1]))
+        );
+        press(&mut picker, KeyCode::Char('a'));
+        assert_eq!(picker.checked.len(), 4);
+        press(&mut picker, KeyCode::Left);
+        assert!(picker.checked.is_empty());
+    }
+
+    #[test]
+    fn select_all_key_checks_only_filtered_options() {
+        let mut picker = picker().with_multi_select(false);
+        type_text(&mut picker, "w");
+        press(&mut picker, KeyCode::Right);
+        assert_eq!(picker.checked, BTreeSet::from([1, 2]));
+        // Once something is typed, `a` filters instead.
+        press(&mut picker, KeyCode::Char('a'));
+        assert_eq!(picker.filter, "wa");
+    }
+
+    #[test]
+    fn escape_skips_and_ctrl_c_interrupts() {
+        let mut picker = picker();
+        assert_eq!(
+            press(&mut picker, KeyCode::Esc),
+            Step::Done(Outcome::Skipped)
+        );
+        assert_eq!(
+            picker.handle(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            Step::Interrupted
+        );
+    }
+
+    #[test]
+    fn set_labels_keeps_filter() {
+        let mut picker = picker();
+        type_text(&mut picker, "w");
+        picker.set_labels(vec!["worker".to_string(), "api".to_string()]);
+        assert_eq!(picker.visible, vec![0]);
+    }
+
+    #[test]
+    fn frame_shows_page_and_help() {
+        let mut picker = picker().with_render_config(RenderConfig::empty());
+        type_text(&mut picker, "w");
+        let frame = picker.frame(80);
+        assert_eq!(frame.len(), 4);
+        assert_eq!(frame[0], "? Pick: w");
+        assert_eq!(frame[1], "> \x1b[4mw\x1b[24meb");
+        assert_eq!(frame[2], "  \x1b[4mw\x1b[24morker");
+        assert_eq!(frame[3], format!("[{SELECT_HELP}]"));
+    }
+
+    #[test]
+    fn frame_shows_page_checkboxes_and_help() {
+        let mut picker = picker()
+            .with_render_config(RenderConfig::empty())
+            .with_multi_select(false);
+        press(&mut picker, KeyCode::Char(' '));
+        let frame = picker.frame(80);
+        assert_eq!(frame.len(), 4);
+        assert_eq!(frame[0], "? Pick: ");
+        assert_eq!(frame[1], "> [x] api");
+        assert_eq!(frame[2], "v [ ] web");
+        assert_eq!(frame[3], format!("[{MULTI_SELECT_HELP}]"));
+    }
+
+    #[test]
+    fn frame_fits_lines_longer_than_the_width() {
+        let mut picker = Picker::<char>::new("Pick:", vec!["界".repeat(20)])
+            .with_render_config(RenderConfig::empty());
+        type_text(&mut picker, "abcdefghijklmnopqrstuvwxyz");
+        let frame = picker.frame(20);
+        // The end of the filter stays visible while typing.
+        assert_eq!(frame[0], "? Pick: opqrstuvwxyz");
+        // Wide chars take two columns each.
+        picker.filter.clear();
+        picker.refilter();
+        let frame = picker.frame(20);
+        assert_eq!(frame[1], format!("> {}", "界".repeat(9)));
+        assert!(frame.iter().all(|line| line.width() <= 20));
+    }
+}
@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{registry, xdg};
+
+/// Directories never searched for repositories: dependency and build output.
+pub const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "vendor",
+    "dist",
+    "build",
+    "__pycache__",
+];
+
+/// How long a scan is reused before the roots are walked again.
+const CACHE_TTL_SECS: u64 = 60 * 60;
+
+fn is_bare_repo(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+fn walk(dir: &Path, depth: usize, max_depth: usize, out: &mut Vec<PathBuf>) {
+    let git = dir.join(".git");
+    if git.is_dir() {
+        // A regular checkout: nested repositories are its own business.
+        out.push(dir.to_path_buf());
+        return;
+    }
+    // Worktrees have a `.git` file, and a bare repository may keep its
+    // worktrees below it, so keep looking inside both.
+    if git.is_file() || is_bare_repo(dir) {
+        out.push(dir.to_path_buf());
+    }
+    if depth == max_depth {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let is_dir = entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false);
+        let name = entry.file_name().to_string_lossy().to_string();
+        if is_dir && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+            walk(&entry.path(), depth + 1, max_depth, out);
+        }
+    }
+}
+
+/// Git repositories, worktrees and bare repositories under `roots`, at most
+/// `max_depth` levels below them, sorted by path.
+pub fn discover(roots: &[PathBuf], max_depth: usize) -> Vec<PathBuf> {
+    let mut projects = Vec::new();
+    for root in roots {
+        walk(root, 0, max_depth, &mut projects);
+    }
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+struct Cache {
+    roots: Vec<PathBuf>,
+    max_depth: usize,
+    scanned_at: u64,
+    projects: Vec<PathBuf>,
+}
+
+pub fn cache_path() -> PathBuf {
+    xdg::state_home().join("zism").join("projects.json")
+}
+
+fn read_cache(path: &Path) -> Option<Cache> {
+    let input = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&input).ok()
+}
+
+fn write_cache(path: &Path, cache: &Cache) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    std::fs::write(path, serde_json::to_string_pretty(cache)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Seconds since the epoch at which `dir` last changed, if known.
+fn modified_at(dir: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(dir).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Like `discover`, but reuses a recent scan of the same roots from `cache`.
+/// A root that changed since the scan (such as a freshly cloned repository
+/// directly inside it) triggers a new one.
+pub fn discover_cached(roots: &[PathBuf], max_depth: usize, cache: &Path) -> Vec<PathBuf> {
+    let now = registry::now();
+    if let Some(cached) = read_cache(cache)
+        && cached.roots == roots
+        && cached.max_depth == max_depth
+        && now.saturating_sub(cached.scanned_at) < CACHE_TTL_SECS
+        && roots
+            .iter()
+            .all(|root| modified_at(root).is_none_or(|at| at < cached.scanned_at))
+    {
+        return cached.projects;
+    }
+    let projects = discover(roots, max_depth);
+    let cached = Cache {
+        roots: roots.to_vec(),
+        max_depth,
+        scanned_at: now,
+        projects,
+    };
+    if let Err(err) = write_cache(cache, &cached) {
+        eprintln!("Warning: {err:#}");
+    }
+    cached.projects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        for dir in [
+            "api/.git",
+            "api/nested/.git",
+            "work/web/.git",
+            "work/web/node_modules/pkg/.git",
+            "work/deep/a/b/.git",
+            "target/cached/.git",
+            ".hidden/repo/.git",
+            "mono/objects",
+            "mono/refs",
+            "mono/feature",
+            "notes",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("mono/HEAD"), "ref: refs/heads/main\n").unwrap();
+        std::fs::write(
+            root.join("mono/feature/.git"),
+            "gitdir: ../worktrees/feature\n",
+        )
+        .unwrap();
+        tmp
+    }
+
+    #[test]
+    fn discover_finds_repos_worktrees_and_bare_repos() {
+        let tmp = tree();
+        let root = tmp.path().to_path_buf();
+        assert_eq!(
+            discover(std::slice::from_ref(&root), 2),
+            vec![
+                root.join("api"),
+                root.join("mono"),
+                root.join("mono/feature"),
+                root.join("work/web"),
+            ]
+        );
+    }
+
+    #[test]
+    fn discover_respects_max_depth() {
+        let tmp = tree();
+        let root = tmp.path().to_path_buf();
+        assert!(!discover(std::slice::from_ref(&root), 3).contains(&root.join("work/deep/a/b")));
+        assert!(discover(std::slice::from_ref(&root), 4).contains(&root.join("work/deep/a/b")));
+        assert_eq!(
+            discover(std::slice::from_ref(&root), 1),
+            vec![root.join("api"), root.join("mono")]
+        );
+    }
+
+    /// Marks `dir` as unchanged for the last day, so a scan made now counts
+    /// as newer than it.
+    fn backdate(dir: &Path) {
+        let day_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(24 * 60 * 60);
+        std::fs::File::open(dir)
+            .unwrap()
+            .set_modified(day_ago)
+            .unwrap();
+    }
+
+    #[test]
+    fn discover_cached_reuses_recent_scan_of_same_roots() {
+        let tmp = tree();
+        let root = tmp.path().to_path_buf();
+        let cache = tempfile::tempdir().unwrap();
+        let cache = cache.path().join("projects.json");
+        let roots = vec![root.clone()];
+        backdate(&root);
+        let first = discover_cached(&roots, 2, &cache);
+        std::fs::create_dir_all(root.join("work/late/.git")).unwrap();
+        backdate(&root);
+        assert_eq!(discover_cached(&roots, 2, &cache), first);
+        assert!(discover_cached(&roots, 3, &cache).contains(&root.join("work/late")));
+    }
+
+    #[test]
+    fn discover_cached_rescans_roots_changed_since_the_scan() {
+        let tmp = tree();
+        let root = tmp.path().to_path_buf();
+        let cache = tempfile::tempdir().unwrap();
+        let cache = cache.path().join("projects.json");
+        let roots = vec![root.clone()];
+        backdate(&root);
+        assert!(!discover_cached(&roots, 2, &cache).contains(&root.join("cloned")));
+        std::fs::create_dir_all(root.join("cloned/.git")).unwrap();
+        assert!(discover_cached(&roots, 2, &cache).contains(&root.join("cloned")));
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::xdg;
+use crate::zellij::Session;
+
+/// What zism remembers about a session. Timestamps are Unix seconds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Entry {
+    /// Directory the session was created in
+    pub directory: Option<PathBuf>,
+    /// Layout the session was created with
+    pub layout: Option<String>,
+    pub created_at: Option<u64>,
+    pub last_attached_at: Option<u64>,
+    /// Number of times the session was created or attached through zism
+    pub attach_count: u32,
+    pub tags: Vec<String>,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Session metadata that Zellij itself does not keep, stored as JSON under
+/// `$XDG_STATE_HOME/zism/`.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Registry {
+    #[serde(default)]
+    sessions: BTreeMap<String, Entry>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+pub fn registry_path() -> PathBuf {
+    xdg::state_home().join("zism").join("sessions.json")
+}
+
+impl Registry {
+    /// Loads the registry, returning an empty one when the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut registry = match std::fs::read_to_string(path) {
+            Ok(input) => serde_json::from_str(&input)
+                .with_context(|| format!("Invalid session registry {}", path.display()))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Registry::default(),
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read {}", path.display()));
+            }
+        };
+        registry.path = path.to_path_buf();
+        Ok(registry)
+    }
+
+    /// Writes the registry through a temporary file so a crash never leaves
+    /// it half-written.
+    pub fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Entry> {
+        self.sessions.get(name)
+    }
+
+    /// Starts a fresh entry for a session created at `now`, replacing whatever
+    /// was recorded for an earlier session with the same name.
+    pub fn record_created(
+        &mut self,
+        name: &str,
+        directory: Option<&Path>,
+        layout: Option<&str>,
+        now: u64,
+    ) {
+        let directory = directory.map(|d| d.canonicalize().unwrap_or_else(|_| d.to_path_buf()));
+        self.sessions.insert(
+            name.to_string(),
+            Entry {
+                directory,
+                layout: layout.map(str::to_string),
+                created_at: Some(now),
+                last_attached_at: Some(now),
+                attach_count: 1,
+                tags: Vec::new(),
+            },
+        );
+    }
+
+    /// Counts an attach to `name` at `now`.
+    pub fn record_attached(&mut self, name: &str, now: u64) {
+        let entry = self.sessions.entry(name.to_string()).or_default();
+        entry.last_attached_at = Some(now);
+        entry.attach_count += 1;
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.sessions.remove(name);
+    }
+
+    pub fn add_tags(&mut self, name: &str, tags: &[String]) {
+        let entry = self.sessions.entry(name.to_string()).or_default();
+        for tag in tags {
+            if !entry.tags.contains(tag) {
+                entry.tags.push(tag.clone());
+            }
+        }
+    }
+
+    pub fn remove_tags(&mut self, name: &str, tags: &[String]) {
+        if let Some(entry) = self.sessions.get_mut(name) {
+            entry.tags.retain(|t| !tags.contains(t));
+        }
+    }
+
+    pub fn rename(&mut self, old: &str, new: &str) {
+        if let Some(entry) = self.sessions.remove(old) {
+            self.sessions.insert(new.to_string(), entry);
+        }
+    }
+
+    /// The running session that was created in `directory`, if any. The
+    /// session zism runs inside of is skipped, as attaching to it is a no-op.
+    pub fn live_session_for_dir<'a>(
+        &self,
+        directory: &Path,
+        sessions: &'a [Session],
+    ) -> Option<&'a Session> {
+        let directory = directory
+            .canonicalize()
+            .unwrap_or_else(|_| directory.to_path_buf());
+        sessions
+            .iter()
+            .filter(|s| !s.exited && !s.current)
+            .find(|s| {
+                self.get(&s.name)
+                    .and_then(|e| e.directory.as_deref())
+                    .is_some_and(|d| d == directory)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(name: &str, exited: bool) -> Session {
+        Session {
+            name: name.to_string(),
+            age: None,
+            exited,
+            current: false,
+        }
+    }
+
+    #[test]
+    fn load_returns_empty_when_file_is_missing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let registry = Registry::load(&tmp.path().join("missing.json")).unwrap();
+        assert_eq!(registry.get("api"), None);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("round-trip.json");
+        let mut registry = Registry::load(&path).unwrap();
+        registry.record_created("api", Some(Path::new("/srv/api")), Some("compact"), now());
+        registry.add_tags("api", &["work".to_string()]);
+        registry.save().unwrap();
+
+        let loaded = Registry::load(&path).unwrap();
+        assert_eq!(loaded.get("api"), registry.get("api"));
+        assert_eq!(
+            loaded.get("api").unwrap().directory,
+            Some(PathBuf::from("/srv/api"))
+        );
+    }
+
+    #[test]
+    fn record_created_sets_timestamps_and_replaces_old_entry() {
+        let mut registry = Registry::default();
+        registry.add_tags("api", &["stale".to_string()]);
+        registry.record_created("api", None, None, now());
+        let entry = registry.get("api").unwrap();
+        assert!(entry.created_at.is_some());
+        assert_eq!(entry.last_attached_at, entry.created_at);
+        assert!(entry.tags.is_empty());
+    }
+
+    #[test]
+    fn record_attached_creates_entry_for_unknown_session() {
+        let mut registry = Registry::default();
+        registry.record_attached("external", now());
+        let entry = registry.get("external").unwrap();
+        assert!(entry.last_attached_at.is_some());
+        assert_eq!(entry.attach_count, 1);
+        assert_eq!(entry.created_at, None);
+    }
+
+    #[test]
+    fn remove_prunes_entry() {
+        let mut registry = Registry::default();
+        registry.record_created("api", None, None, now());
+        registry.remove("api");
+        assert_eq!(registry.get("api"), None);
+    }
+
+    #[test]
+    fn tags_are_added_once_and_removed() {
+        let mut registry = Registry::default();
+        let tags = vec!["work".to_string(), "api".to_string()];
+        registry.add_tags("s", &tags);
+        registry.add_tags("s", &tags[..1]);
+        assert_eq!(registry.get("s").unwrap().tags, tags);
+        registry.remove_tags("s", &tags[..1]);
+        assert_eq!(registry.get("s").unwrap().tags, vec!["api".to_string()]);
+    }
+
+    #[test]
+    fn load_rejects_corrupt_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("corrupt.json");
+        std::fs::write(&path, "{not json").unwrap();
+        assert!(Registry::load(&path).is_err());
+    }
+
+    #[test]
+    fn rename_moves_entry() {
+        let mut registry = Registry::default();
+        registry.record_created("old", Some(Path::new("/srv/api")), None, now());
+        registry.rename("old", "new");
+        assert_eq!(registry.get("old"), None);
+        assert!(registry.get("new").is_some());
+    }
+
+    #[test]
+    fn live_session_for_dir_ignores_exited_current_and_other_dirs() {
+        let mut registry = Registry::default();
+        registry.record_created("dead", Some(Path::new("/srv/api")), None, now());
+        registry.record_created("here", Some(Path::new("/srv/api")), None, now());
+        registry.record_created("live", Some(Path::new("/srv/api")), None, now());
+        registry.record_created("web", Some(Path::new("/srv/web")), None, now());
+        let sessions = [
+            session("dead", true),
+            Session {
+                current: true,
+                ..session("here", false)
+            },
+            session("web", false),
+            session("live", false),
+        ];
+        let found = registry.live_session_for_dir(Path::new("/srv/api"), &sessions);
+        assert_eq!(found.map(|s| s.name.as_str()), Some("live"));
+        assert_eq!(
+            registry.live_session_for_dir(Path::new("/srv/other"), &sessions),
+            None
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::cmp::Reverse;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::registry::{Entry, Registry};
+use crate::zellij::Session;
+
+const HOUR: u64 = 3_600;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Order of sessions in the pickers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    /// Most recently attached first
+    #[default]
+    Recent,
+    /// Newest first
+    Created,
+    /// By name
+    Alphabetical,
+    /// Attached often and recently first
+    Frecency,
+}
+
+impl std::fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortOrder::Recent => write!(f, "recent"),
+            SortOrder::Created => write!(f, "created"),
+            SortOrder::Alphabetical => write!(f, "alphabetical"),
+            SortOrder::Frecency => write!(f, "frecency"),
+        }
+    }
+}
+
+/// Attach count weighted by how long ago the last attach was, in the
+/// spirit of zoxide.
+pub fn frecency(entry: &Entry, now: u64) -> u64 {
+    let Some(last) = entry.last_attached_at else {
+        return 0;
+    };
+    let weight = match now.saturating_sub(last) {
+        age if age < HOUR => 8,
+        age if age < DAY => 4,
+        age if age < WEEK => 2,
+        _ => 1,
+    };
+    u64::from(entry.attach_count) * weight
+}
+
+impl SortOrder {
+    pub fn next(self) -> Self {
+        match self {
+            SortOrder::Recent => SortOrder::Created,
+            SortOrder::Created => SortOrder::Alphabetical,
+            SortOrder::Alphabetical => SortOrder::Frecency,
+            SortOrder::Frecency => SortOrder::Recent,
+        }
+    }
+
+    /// Sorts in place. Ties and sessions without recorded data keep the order
+    /// Zellij listed them in.
+    pub fn sort(self, sessions: &mut [Session], registry: &Registry, now: u64) {
+        let entry = |s: &Session| registry.get(&s.name).cloned().unwrap_or_default();
+        match self {
+            SortOrder::Recent => {
+                sessions.sort_by_cached_key(|s| Reverse(entry(s).last_attached_at));
+            }
+            SortOrder::Created => {
+                sessions.sort_by_key(|s| (s.age.is_none(), s.age));
+            }
+            SortOrder::Alphabetical => sessions.sort_by(|a, b| a.name.cmp(&b.name)),
+            SortOrder::Frecency => {
+                sessions.sort_by_cached_key(|s| Reverse(frecency(&entry(s), now)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const NOW: u64 = 10_000_000;
+
+    fn session(name: &str, age_secs: Option<u64>) -> Session {
+        Session {
+            name: name.to_string(),
+            age: age_secs.map(Duration::from_secs),
+            exited: false,
+            current: false,
+        }
+    }
+
+    fn names(sessions: &[Session]) -> Vec<&str> {
+        sessions.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    fn registry() -> Registry {
+        let mut registry = Registry::default();
+        for _ in 0..50 {
+            registry.record_attached("old-favorite", NOW - 2 * WEEK);
+        }
+        for _ in 0..3 {
+            registry.record_attached("today", NOW - 2 * HOUR);
+        }
+        registry.record_created("just-now", None, None, NOW - 60);
+        registry
+    }
+
+    fn sessions() -> Vec<Session> {
+        vec![
+            session("old-favorite", Some(300)),
+            session("unknown", None),
+            session("today", Some(100)),
+            session("just-now", Some(200)),
+        ]
+    }
+
+    #[test]
+    fn next_cycles_through_all_orders() {
+        let mut order = SortOrder::Recent;
+        let mut seen = vec![order];
+        for _ in 0..3 {
+            order = order.next();
+            seen.push(order);
+        }
+        assert_eq!(order.next(), SortOrder::Recent);
+        assert_eq!(
+            seen,
+            vec![
+                SortOrder::Recent,
+                SortOrder::Created,
+                SortOrder::Alphabetical,
+                SortOrder::Frecency,
+            ]
+        );
+    }
+
+    #[test]
+    fn recent_puts_last_attached_first_and_unknown_last() {
+        let mut sessions = sessions();
+        SortOrder::Recent.sort(&mut sessions, &registry(), NOW);
+        assert_eq!(
+            names(&sessions),
+            vec!["just-now", "today", "old-favorite", "unknown"]
+        );
+    }
+
+    #[test]
+    fn created_puts_newest_first() {
+        let mut sessions = sessions();
+        SortOrder::Created.sort(&mut sessions, &registry(), NOW);
+        assert_eq!(
+            names(&sessions),
+            vec!["today", "just-now", "old-favorite", "unknown"]
+        );
+    }
+
+    #[test]
+    fn alphabetical_sorts_by_name() {
+        let mut sessions = sessions();
+        SortOrder::Alphabetical.sort(&mut sessions, &registry(), NOW);
+        assert_eq!(
+            names(&sessions),
+            vec!["just-now", "old-favorite", "today", "unknown"]
+        );
+    }
+
+    #[test]
+    fn frecency_weighs_count_by_recency() {
+        let mut sessions = sessions();
+        SortOrder::Frecency.sort(&mut sessions, &registry(), NOW);
+        assert_eq!(
+            names(&sessions),
+            vec!["old-favorite", "today", "just-now", "unknown"]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::naming::{self, NamingStrategy};
+use crate::xdg;
+
+/// A named recipe for a session, written as `[templates.<name>]` in the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Template {
+    /// Directory the session starts in
+    pub directory: Option<PathBuf>,
+    /// Layout name or path; cannot be combined with `commands`
+    pub layout: Option<String>,
+    /// Naming strategy applied to `directory`; defaults to the template name
+    pub name: Option<NamingStrategy>,
+    /// Environment variables set for the session; `~` and `$VAR` are expanded
+    pub env: BTreeMap<String, String>,
+    /// Commands started in their own panes
+    pub commands: Vec<String>,
+}
+
+impl Template {
+    /// Session name for this template, derived from its directory when it
+    /// has a naming strategy.
+    pub fn session_name(&self, template_name: &str) -> String {
+        match (&self.name, &self.directory) {
+            (Some(strategy), Some(dir)) => naming::derive_name(strategy, dir),
+            (Some(strategy), None) => {
+                let cwd = std::env::current_dir().unwrap_or_else(|_| xdg::home_dir());
+                naming::derive_name(strategy, &cwd)
+            }
+            (None, _) => naming::sanitize(template_name),
+        }
+    }
+
+    /// Layout to start the session with: the configured one, or a layout
+    /// generated from `commands` and written under the state directory.
+    pub fn resolve_layout(&self, template_name: &str) -> Result<Option<String>> {
+        if self.commands.is_empty() {
+            return Ok(self.layout.clone());
+        }
+        let path = layouts_dir().join(format!("{}.kdl", naming::sanitize(template_name)));
+        write_layout(&path, &commands_layout(&self.commands))?;
+        Ok(Some(path.display().to_string()))
+    }
+}
+
+fn layouts_dir() -> PathBuf {
+    xdg::state_home().join("zism").join("layouts")
+}
+
+fn write_layout(path: &Path, layout: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    std::fs::write(path, layout).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn kdl_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// KDL layout with one pane per command, between the usual tab and status bars.
+pub fn commands_layout(commands: &[String]) -> String {
+    let mut out = String::from(
+        "layout {\n    default_tab_template {\n        pane size=1 borderless=true {\n            plugin location=\"zellij:tab-bar\"\n        }\n        children\n        pane size=2 borderless=true {\n            plugin location=\"zellij:status-bar\"\n        }\n    }\n    tab {\n",
+    );
+    for command in commands {
+        out.push_str(&format!(
+            "        pane name={} command=\"sh\" {{\n            args \"-c\" {}\n        }}\n",
+            kdl_string(command),
+            kdl_string(command)
+        ));
+    }
+    out.push_str("    }\n}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::testutil::restore_var;
+
+    #[test]
+    fn session_name_defaults_to_template_name() {
+        assert_eq!(Template::default().session_name("on call"), "on-call");
+    }
+
+    #[test]
+    fn session_name_derives_from_directory() {
+        let template = Template {
+            directory: Some(PathBuf::from("/srv/ops")),
+            name: Some(NamingStrategy::Template("{basename}-oncall".to_string())),
+            ..Template::default()
+        };
+        assert_eq!(template.session_name("oncall"), "ops-oncall");
+    }
+
+    #[test]
+    fn resolve_layout_keeps_configured_layout_without_commands() {
+        let template = Template {
+            layout: Some("compact".to_string()),
+            ..Template::default()
+        };
+        assert_eq!(
+            template.resolve_layout("oncall").unwrap(),
+            Some("compact".to_string())
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn resolve_layout_writes_commands_layout_to_state_dir() {
+        let state = tempfile::tempdir().unwrap();
+        let previous = std::env::var_os("XDG_STATE_HOME");
+        unsafe { std::env::set_var("XDG_STATE_HOME", state.path()) };
+        let template = Template {
+            commands: vec!["k9s".to_string()],
+            ..Template::default()
+        };
+        let layout = template.resolve_layout("on call");
+        restore_var("XDG_STATE_HOME", previous);
+        let path = state.path().join("zism/layouts/on-call.kdl");
+        assert_eq!(layout.unwrap(), Some(path.display().to_string()));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            commands_layout(&template.commands)
+        );
+    }
+
+    #[test]
+    fn commands_layout_runs_each_command_in_a_pane() {
+        let layout = commands_layout(&["tail -f log".to_string(), "k9s".to_string()]);
+        assert!(layout.contains("pane name=\"tail -f log\" command=\"sh\""));
+        assert!(layout.contains("args \"-c\" \"tail -f log\""));
+        assert!(layout.contains("args \"-c\" \"k9s\""));
+    }
+
+    #[test]
+    fn kdl_string_escapes_quotes_and_backslashes() {
+        assert_eq!(kdl_string(r#"echo "a\b""#), r#""echo \"a\\b\"""#);
+    }
+}
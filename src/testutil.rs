@@ -0,0 +1,12 @@
+//! Helpers shared by the unit tests.
+
+use std::ffi::OsString;
+
+/// Puts back an environment variable saved with `var_os` before the test
+/// changed it, so tests do not leak their settings into each other.
+pub fn restore_var(name: &str, previous: Option<OsString>) {
+    match previous {
+        Some(value) => unsafe { std::env::set_var(name, value) },
+        None => unsafe { std::env::remove_var(name) },
+    }
+}
@@ -1,11 +1,21 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Result, bail};
+use crossterm::event::KeyCode;
 use inquire::ui::{Attributes, Color, RenderConfig, StyleSheet};
 use inquire::validator::Validation;
-use inquire::{Autocomplete, CustomUserError, Select, Text};
+use inquire::{Autocomplete, Confirm, CustomUserError, InquireError, Text};
 
 use crate::action::Action;
+use crate::fuzzy;
+use crate::paths;
+use crate::picker::{Outcome, Picker};
+use crate::registry::{self, Registry};
+use crate::sort::SortOrder;
+use crate::template::Template;
+use crate::xdg;
+use crate::zellij::{self, NameError, Session};
 
 fn render_config() -> RenderConfig<'static> {
     RenderConfig {
@@ -19,63 +29,406 @@ fn render_config() -> RenderConfig<'static> {
     }
 }
 
-pub fn available_actions(has_sessions: bool) -> Vec<Action> {
-    if has_sessions {
-        vec![
-            Action::CreateWithDir,
-            Action::Create,
-            Action::Attach,
-            Action::Delete,
-        ]
-    } else {
-        vec![Action::CreateWithDir, Action::Create]
+pub fn available_actions(sessions: &[Session], has_templates: bool) -> Vec<Action> {
+    let mut actions = vec![Action::CreateWithDir, Action::Create];
+    if has_templates {
+        actions.push(Action::FromTemplate);
+    }
+    // The session zism runs in can be renamed, but not attached to, killed
+    // or deleted from inside itself.
+    let others = || sessions.iter().filter(|s| !s.current);
+    if others().any(|s| !s.exited) {
+        actions.push(Action::Attach);
+    }
+    if sessions.iter().any(|s| s.exited) {
+        actions.push(Action::Resurrect);
+    }
+    if sessions.iter().any(|s| !s.exited) {
+        actions.push(Action::Rename);
     }
+    if others().any(|s| !s.exited) {
+        actions.push(Action::Kill);
+    }
+    if others().next().is_some() {
+        actions.push(Action::Delete);
+    }
+    actions
 }
 
-pub fn select_action(has_sessions: bool) -> Result<Action> {
-    let options = available_actions(has_sessions);
-    let action = Select::new("Select an action:", options)
+pub fn select_action(
+    sessions: &[Session],
+    has_templates: bool,
+    default: Option<Action>,
+) -> Result<Action> {
+    let actions = available_actions(sessions, has_templates);
+    let cursor = default
+        .and_then(|d| actions.iter().position(|a| *a == d))
+        .unwrap_or(0);
+    let labels = actions.iter().map(|action| action.to_string()).collect();
+    let picker = Picker::new("Select an action:", labels)
         .with_render_config(render_config())
-        .with_vim_mode(true)
-        .prompt()?;
-    Ok(action)
+        .with_starting_cursor(cursor);
+    Ok(actions[pick(picker)?])
+}
+
+/// Index of the option picked in `picker`, or `None` when it was skipped
+/// with ESC.
+fn pick_skippable(mut picker: Picker<'_, ()>) -> Result<Option<usize>> {
+    match picker.prompt()? {
+        Outcome::Picked(picked) => Ok(picked.first().copied()),
+        Outcome::Key(()) | Outcome::Skipped => Ok(None),
+    }
+}
+
+/// Like `pick_skippable`, but ESC cancels the flow.
+fn pick(picker: Picker<'_, ()>) -> Result<usize> {
+    match pick_skippable(picker)? {
+        Some(index) => Ok(index),
+        None => Err(InquireError::OperationCanceled.into()),
+    }
+}
+
+/// Zellij only reports when a session was created, so the age is labelled as
+/// such rather than read as the time an exited session died.
+fn session_label(session: &Session, name_width: usize) -> String {
+    let age = session
+        .age
+        .map(|age| format!("created {} ago", zellij::format_age(age)))
+        .unwrap_or_default();
+    format!(
+        "{:<name_width$}  {:<7}  {age}",
+        session.name,
+        session.state()
+    )
+    .trim_end()
+    .to_string()
+}
+
+/// Picker labels for `sessions`, with the state and age columns aligned.
+fn session_labels(sessions: &[Session]) -> Vec<String> {
+    let name_width = sessions
+        .iter()
+        .map(|s| s.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    sessions
+        .iter()
+        .map(|session| session_label(session, name_width))
+        .collect()
 }
 
-pub fn select_session(sessions: &[String], action: Action) -> Result<String> {
+fn sort_help(order: SortOrder) -> String {
+    format!(
+        "↑↓ to move, enter to select, type to filter, TAB to sort by {}",
+        order.next()
+    )
+}
+
+/// Picks one session. TAB cycles through the sort orders, keeping the filter.
+pub fn select_session(
+    sessions: &[Session],
+    order: SortOrder,
+    registry: &Registry,
+    page_size: usize,
+    action: Action,
+) -> Result<Session> {
     if sessions.is_empty() {
         bail!("No sessions available to select.");
     }
-    let session = Select::new("Select a session:", sessions.to_vec())
+    let sorted = |order: SortOrder| {
+        let mut sorted = sessions.to_vec();
+        order.sort(&mut sorted, registry, registry::now());
+        sorted
+    };
+    let mut order = order;
+    let mut sessions = sorted(order);
+    let mut picker = Picker::new("Select a session:", session_labels(&sessions))
         .with_render_config(action.render_config())
-        .with_vim_mode(true)
-        .prompt()?;
-    Ok(session)
+        .with_page_size(page_size)
+        .with_help_message(&sort_help(order))
+        .with_key(KeyCode::Tab, ());
+    loop {
+        match picker.prompt()? {
+            Outcome::Picked(picked) => return Ok(sessions.swap_remove(picked[0])),
+            Outcome::Key(()) => {
+                order = order.next();
+                sessions = sorted(order);
+                picker.set_labels(session_labels(&sessions));
+                picker.set_help_message(&sort_help(order));
+            }
+            Outcome::Skipped => return Err(InquireError::OperationCanceled.into()),
+        }
+    }
+}
+
+/// Which sessions the bulk delete picker offers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionFilter {
+    Exited,
+    Running,
+    All,
+}
+
+impl SessionFilter {
+    pub fn matches(self, session: &Session) -> bool {
+        match self {
+            SessionFilter::Exited => session.exited,
+            SessionFilter::Running => !session.exited,
+            SessionFilter::All => true,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SessionFilter::Exited => "Exited sessions",
+            SessionFilter::Running => "Running sessions (kill and delete)",
+            SessionFilter::All => "All sessions (kill running ones)",
+        }
+    }
 }
 
-pub fn select_session_optional(sessions: &[String], action: Action) -> Result<Option<String>> {
+/// Filters worth offering. Deleting targets exited sessions unless the user
+/// explicitly picks a filter that includes running ones.
+pub fn session_filters(sessions: &[Session]) -> Vec<SessionFilter> {
+    let exited = sessions.iter().filter(|s| s.exited).count();
+    let running = sessions.len() - exited;
+    let mut filters = Vec::new();
+    if exited > 0 {
+        filters.push(SessionFilter::Exited);
+    }
+    if running > 0 {
+        filters.push(SessionFilter::Running);
+    }
+    if exited > 0 && running > 0 {
+        filters.push(SessionFilter::All);
+    }
+    filters
+}
+
+pub fn select_session_filter(sessions: &[Session], action: Action) -> Result<SessionFilter> {
+    let filters = session_filters(sessions);
+    if filters == [SessionFilter::Exited] {
+        return Ok(SessionFilter::Exited);
+    }
+    let labels = filters
+        .iter()
+        .map(|filter| {
+            let count = sessions.iter().filter(|s| filter.matches(s)).count();
+            format!("{} ({count})", filter.label())
+        })
+        .collect();
+    let picker = Picker::new("Which sessions?", labels).with_render_config(action.render_config());
+    Ok(filters[pick(picker)?])
+}
+
+/// Multi-select picker. Returns an empty list when nothing was chosen or the
+/// prompt was skipped with ESC.
+pub fn select_sessions(
+    sessions: &[Session],
+    preselect_all: bool,
+    order: SortOrder,
+    registry: &Registry,
+    page_size: usize,
+    action: Action,
+) -> Result<Vec<Session>> {
     if sessions.is_empty() {
-        return Ok(None);
+        return Ok(Vec::new());
     }
-    let answer = Select::new("Select a session:", sessions.to_vec())
+    let mut sorted = sessions.to_vec();
+    order.sort(&mut sorted, registry, registry::now());
+    let names = sorted.iter().map(|s| s.name.clone()).collect();
+    let outcome = Picker::<()>::new("Select sessions:", session_labels(&sorted))
         .with_render_config(action.render_config())
-        .with_vim_mode(true)
-        .prompt_skippable()?;
+        .with_page_size(page_size)
+        .with_answers(names)
+        .with_multi_select(preselect_all)
+        .prompt()?;
+    match outcome {
+        Outcome::Picked(picked) => Ok(picked.into_iter().map(|i| sorted[i].clone()).collect()),
+        Outcome::Key(()) | Outcome::Skipped => Ok(Vec::new()),
+    }
+}
+
+fn template_label(name: &str, template: &Template, name_width: usize) -> String {
+    let dir = template
+        .directory
+        .as_ref()
+        .map(|dir| dir.display().to_string())
+        .unwrap_or_default();
+    format!("{name:<name_width$}  {dir}").trim_end().to_string()
+}
+
+/// Picks one of the configured session templates.
+pub fn select_template(
+    templates: &BTreeMap<String, Template>,
+    page_size: usize,
+    action: Action,
+) -> Result<(&str, &Template)> {
+    let name_width = templates
+        .keys()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let templates: Vec<_> = templates.iter().collect();
+    let labels = templates
+        .iter()
+        .map(|(name, template)| template_label(name, template, name_width))
+        .collect();
+    let picker = Picker::new("Select a template:", labels)
+        .with_render_config(action.render_config())
+        .with_page_size(page_size);
+    let (name, template) = templates[pick(picker)?];
+    Ok((name.as_str(), template))
+}
+
+/// `path` with a leading `home` shown as `~`.
+fn tilde_path(path: &Path, home: &Path) -> String {
+    match path.strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => "~".to_string(),
+        Ok(rest) => format!("~/{}", rest.display()),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// Picks a discovered project. Returns `None` when the user would rather
+/// type a path.
+pub fn select_project(
+    projects: &[PathBuf],
+    page_size: usize,
+    action: Action,
+) -> Result<Option<PathBuf>> {
+    let home = xdg::home_dir();
+    // The last entry leads to the path prompt.
+    let labels = projects
+        .iter()
+        .map(|dir| tilde_path(dir, &home))
+        .chain(["✎ type a path...".to_string()])
+        .collect();
+    let picker = Picker::new("Select a project:", labels)
+        .with_render_config(action.render_config())
+        .with_page_size(page_size)
+        .with_pinned_last();
+    Ok(projects.get(pick(picker)?).cloned())
+}
+
+/// Picks a layout for a new session. ESC keeps Zellij's default layout.
+pub fn select_layout(
+    layouts: &[String],
+    default: Option<&str>,
+    page_size: usize,
+    action: Action,
+) -> Result<Option<String>> {
+    let cursor = default
+        .and_then(|d| layouts.iter().position(|l| l == d))
+        .unwrap_or(0);
+    let picker = Picker::new("Select a layout:", layouts.to_vec())
+        .with_render_config(action.render_config())
+        .with_page_size(page_size)
+        .with_starting_cursor(cursor)
+        .with_help_message("ESC to use Zellij's default layout");
+    Ok(pick_skippable(picker)?.map(|index| layouts[index].clone()))
+}
+
+pub fn confirm(message: &str, action: Action) -> Result<bool> {
+    let answer = Confirm::new(message)
+        .with_render_config(action.render_config())
+        .with_default(true)
+        .prompt()?;
     Ok(answer)
 }
 
-pub fn input_session_name(action: Action) -> Result<String> {
-    let name = Text::new("Enter new session name:")
+pub fn confirm_force_run_commands(action: Action) -> Result<bool> {
+    let force = Confirm::new("Run pane commands immediately?")
         .with_render_config(action.render_config())
-        .with_validator(|input: &str| Ok(validate_session_name(input)))
+        .with_default(false)
+        .with_help_message("Without this, resurrected commands wait for ENTER")
         .prompt()?;
-    Ok(name)
+    Ok(force)
+}
+
+fn session_names(sessions: &[Session]) -> Vec<String> {
+    sessions.iter().map(|s| s.name.clone()).collect()
+}
+
+/// Offers the next free `name-N` when the typed name is already taken, so
+/// TAB accepts the suggestion.
+#[derive(Clone)]
+struct NameSuggester {
+    existing: Vec<String>,
+}
+
+impl Autocomplete for NameSuggester {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        match zellij::validate_session_name(input, &self.existing) {
+            Err(NameError::Exists { suggestion }) => Ok(vec![suggestion]),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<Option<String>, CustomUserError> {
+        if highlighted_suggestion.is_some() {
+            return Ok(highlighted_suggestion);
+        }
+        Ok(self.get_suggestions(input)?.into_iter().next())
+    }
 }
 
-fn validate_session_name(name: &str) -> Validation {
-    if name.trim().is_empty() {
-        Validation::Invalid("Session name cannot be empty.".into())
+/// Prompts for a session name that Zellij accepts and `existing` does not
+/// already use. `initial` pre-fills the prompt.
+fn prompt_session_name(
+    existing: Vec<String>,
+    initial: Option<&str>,
+    action: Action,
+) -> Result<String> {
+    let validator_existing = existing.clone();
+    let mut prompt = Text::new("Enter new session name:")
+        .with_render_config(action.render_config())
+        .with_autocomplete(NameSuggester { existing })
+        .with_validator(move |input: &str| Ok(validate_session_name(input, &validator_existing)))
+        .with_help_message("TAB accepts the suggested name when one is taken");
+    if let Some(initial) = initial {
+        prompt = prompt.with_initial_value(initial);
+    }
+    Ok(prompt.prompt()?)
+}
+
+pub fn input_session_name(sessions: &[Session], action: Action) -> Result<String> {
+    prompt_session_name(session_names(sessions), None, action)
+}
+
+/// Lets the user edit a name derived from a directory before creating the
+/// session. A derived name that is already taken is replaced with the next
+/// free `name-N`.
+pub fn input_derived_session_name(
+    derived: &str,
+    sessions: &[Session],
+    action: Action,
+) -> Result<String> {
+    let existing = session_names(sessions);
+    let initial = if existing.iter().any(|name| name == derived) {
+        zellij::suggest_available_name(derived, &existing)
     } else {
-        Validation::Valid
+        derived.to_string()
+    };
+    prompt_session_name(existing, Some(&initial), action)
+}
+
+pub fn input_new_name(session: &Session, sessions: &[Session], action: Action) -> Result<String> {
+    let others: Vec<String> = session_names(sessions)
+        .into_iter()
+        .filter(|name| *name != session.name)
+        .collect();
+    prompt_session_name(others, Some(&session.name), action)
+}
+
+fn validate_session_name(name: &str, existing: &[String]) -> Validation {
+    match zellij::validate_session_name(name, existing) {
+        Ok(()) => Validation::Valid,
+        Err(err) => Validation::Invalid(err.to_string().into()),
     }
 }
 
@@ -102,14 +455,16 @@ pub fn longest_common_prefix(items: &[String]) -> String {
 #[derive(Clone)]
 struct DirCompleter {
     home: PathBuf,
+    /// Also list hidden directories when the typed name has no leading `.`
+    show_hidden: bool,
 }
 
 impl DirCompleter {
-    fn new(home: PathBuf) -> Self {
-        Self { home }
+    fn new(home: PathBuf, show_hidden: bool) -> Self {
+        Self { home, show_hidden }
     }
 
-    fn list_dirs(&self, dir: &Path) -> Vec<String> {
+    fn list_dirs(&self, dir: &Path, include_hidden: bool) -> Vec<String> {
         let Ok(entries) = std::fs::read_dir(dir) else {
             return Vec::new();
         };
@@ -118,7 +473,7 @@ impl DirCompleter {
             .filter(|e| e.file_type().map(|ft| ft.is_dir()).unwrap_or(false))
             .filter_map(|e| {
                 let name = e.file_name().to_string_lossy().to_string();
-                if name.starts_with('.') {
+                if name.starts_with('.') && !include_hidden {
                     None
                 } else {
                     Some(name)
@@ -132,39 +487,28 @@ impl DirCompleter {
 
 impl Autocomplete for DirCompleter {
     fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
-        let (dir, prefix) = if input.is_empty() || input.ends_with('/') {
-            (self.home.join(input), String::new())
-        } else {
-            let path = Path::new(input);
-            let parent = path
-                .parent()
-                .map(|p| self.home.join(p))
-                .unwrap_or_else(|| self.home.clone());
-            let file = path
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-            (parent, file)
-        };
+        // A bare `~`, `~user` or `$VAR` naming a directory completes to itself,
+        // so TAB adds the slash.
+        if !input.contains('/')
+            && (input.starts_with('~') || input.starts_with('$'))
+            && paths::resolve(input, &self.home).is_dir()
+        {
+            return Ok(vec![input.to_string()]);
+        }
 
-        let base = if input.is_empty() || !input.contains('/') {
-            String::new()
-        } else if input.ends_with('/') {
-            input.to_string()
-        } else {
-            let parent = Path::new(input).parent().unwrap_or(Path::new(""));
-            if parent.as_os_str().is_empty() {
-                String::new()
-            } else {
-                format!("{}/", parent.display())
-            }
+        // Suggestions keep the typed directory part as is, so `~/` and
+        // `$WORK/` stay unexpanded in the prompt.
+        let (base, prefix) = match input.rfind('/') {
+            Some(i) => input.split_at(i + 1),
+            None => ("", input),
         };
+        let dir = paths::resolve(base, &self.home);
+        let include_hidden = self.show_hidden || prefix.starts_with('.');
 
         let suggestions = self
-            .list_dirs(&dir)
+            .list_dirs(&dir, include_hidden)
             .into_iter()
-            .filter(|name| prefix.is_empty() || name.starts_with(&prefix))
+            .filter(|name| prefix.is_empty() || name.starts_with(prefix))
             .map(|name| format!("{base}{name}"))
             .collect();
 
@@ -197,41 +541,161 @@ impl Autocomplete for DirCompleter {
     }
 }
 
-pub fn input_directory(page_size: usize, action: Action) -> Result<PathBuf> {
-    let home = PathBuf::from(std::env::var("HOME").unwrap_or_default());
-    let input = Text::new("Directory (TAB to complete):")
-        .with_render_config(action.render_config())
-        .with_autocomplete(DirCompleter::new(home.clone()))
-        .with_page_size(page_size)
-        .with_validator(|input: &str| {
-            if input.trim().is_empty() {
-                Ok(Validation::Invalid(
-                    "Directory path cannot be empty.".into(),
-                ))
-            } else {
-                Ok(Validation::Valid)
+/// Offers ranked directories (from zoxide) before anything is typed, then
+/// path completions followed by the ranked directories matching the input.
+#[derive(Clone)]
+struct DirectorySuggester {
+    completer: DirCompleter,
+    ranked: Vec<String>,
+}
+
+impl Autocomplete for DirectorySuggester {
+    fn get_suggestions(&mut self, input: &str) -> Result<Vec<String>, CustomUserError> {
+        if input.is_empty() && !self.ranked.is_empty() {
+            return Ok(self.ranked.clone());
+        }
+        let mut suggestions = self.completer.get_suggestions(input)?;
+        for dir in &self.ranked {
+            if fuzzy::fuzzy_match(input, dir).is_some() && !suggestions.contains(dir) {
+                suggestions.push(dir.clone());
             }
-        })
-        .with_help_message("Type a path relative to ~ and press TAB to complete")
-        .prompt()?;
+        }
+        Ok(suggestions)
+    }
 
-    let input = input.trim_end_matches('/');
-    Ok(home.join(input))
+    fn get_completion(
+        &mut self,
+        input: &str,
+        highlighted_suggestion: Option<String>,
+    ) -> Result<Option<String>, CustomUserError> {
+        self.completer.get_completion(input, highlighted_suggestion)
+    }
+}
+
+/// Prompts for a directory. `ranked` directories are suggested first; when
+/// it is empty the prompt falls back to plain path completion.
+pub fn input_directory(
+    page_size: usize,
+    base_dir: &Path,
+    ranked: &[PathBuf],
+    show_hidden: bool,
+    action: Action,
+) -> Result<PathBuf> {
+    let home = base_dir.to_path_buf();
+    let suggester = DirectorySuggester {
+        completer: DirCompleter::new(home.clone(), show_hidden),
+        ranked: ranked.iter().map(|dir| dir.display().to_string()).collect(),
+    };
+    let help = if ranked.is_empty() {
+        "Type a path (relative, absolute, ~ or $VAR) and press TAB to complete"
+    } else {
+        "Pick a frequent directory, or type a path and press TAB to complete"
+    };
+    let mut input = String::new();
+    loop {
+        let validator_home = home.clone();
+        input = Text::new("Directory (TAB to complete):")
+            .with_render_config(action.render_config())
+            .with_autocomplete(suggester.clone())
+            .with_page_size(page_size)
+            .with_initial_value(&input)
+            .with_validator(move |input: &str| Ok(validate_directory(input, &validator_home)))
+            .with_help_message(help)
+            .prompt()?;
+
+        let dir = paths::resolve(input.trim(), &home);
+        // The directory can change after validation, and creating it can
+        // fail, so problems here are reported and the prompt is shown again.
+        let status = match paths::check_dir(&dir) {
+            Ok(status) => status,
+            Err(err) => {
+                eprintln!("{err:#}");
+                continue;
+            }
+        };
+        if status == paths::DirStatus::Missing {
+            let message = format!("{} does not exist. Create it?", dir.display());
+            if !confirm(&message, action)? {
+                continue;
+            }
+            if let Err(err) = paths::create_dir(&dir) {
+                eprintln!("{err:#}");
+                continue;
+            }
+        }
+        return paths::existing_dir(&dir);
+    }
+}
+
+/// Accepts usable directories and missing ones, which the caller offers to
+/// create.
+fn validate_directory(input: &str, home: &Path) -> Validation {
+    if input.trim().is_empty() {
+        return Validation::Invalid("Directory path cannot be empty.".into());
+    }
+    match paths::check_dir(&paths::resolve(input.trim(), home)) {
+        Ok(_) => Validation::Valid,
+        Err(err) => Validation::Invalid(format!("{err:#}").into()),
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use super::*;
 
+    fn session(name: &str, exited: bool) -> Session {
+        Session {
+            name: name.to_string(),
+            age: None,
+            exited,
+            current: false,
+        }
+    }
+
     #[test]
     fn available_actions_with_sessions_returns_all() {
-        let actions = available_actions(true);
+        let actions = available_actions(&[session("live", false), session("dead", true)], false);
         assert_eq!(
             actions,
             vec![
                 Action::CreateWithDir,
                 Action::Create,
                 Action::Attach,
+                Action::Resurrect,
+                Action::Rename,
+                Action::Kill,
+                Action::Delete,
+            ]
+        );
+    }
+
+    #[test]
+    fn available_actions_with_only_running_sessions_omits_resurrect() {
+        let actions = available_actions(&[session("live", false)], false);
+        assert_eq!(
+            actions,
+            vec![
+                Action::CreateWithDir,
+                Action::Create,
+                Action::Attach,
+                Action::Rename,
+                Action::Kill,
+                Action::Delete,
+            ]
+        );
+    }
+
+    #[test]
+    fn available_actions_with_only_exited_sessions_omits_attach_and_kill() {
+        let actions = available_actions(&[session("dead", true)], false);
+        assert_eq!(
+            actions,
+            vec![
+                Action::CreateWithDir,
+                Action::Create,
+                Action::Resurrect,
                 Action::Delete,
             ]
         );
@@ -239,14 +703,154 @@ mod tests {
 
     #[test]
     fn available_actions_without_sessions_returns_create_and_create_with_dir() {
-        let actions = available_actions(false);
+        let actions = available_actions(&[], false);
         assert_eq!(actions, vec![Action::CreateWithDir, Action::Create]);
     }
 
     #[test]
-    fn select_session_optional_returns_none_when_empty() {
-        let result = select_session_optional(&[], Action::Delete).unwrap();
-        assert_eq!(result, None);
+    fn available_actions_inside_only_session_offers_create_and_rename() {
+        let current = Session {
+            current: true,
+            ..session("here", false)
+        };
+        let actions = available_actions(&[current], false);
+        assert_eq!(
+            actions,
+            vec![Action::CreateWithDir, Action::Create, Action::Rename]
+        );
+    }
+
+    #[test]
+    fn available_actions_with_templates_offers_from_template() {
+        let actions = available_actions(&[], true);
+        assert_eq!(
+            actions,
+            vec![Action::CreateWithDir, Action::Create, Action::FromTemplate]
+        );
+    }
+
+    #[test]
+    fn template_label_aligns_directory_column() {
+        let template = Template {
+            directory: Some(PathBuf::from("/srv/ops")),
+            ..Template::default()
+        };
+        assert_eq!(template_label("oncall", &template, 8), "oncall    /srv/ops");
+        assert_eq!(
+            template_label("scratch", &Template::default(), 8),
+            "scratch"
+        );
+    }
+
+    #[test]
+    fn select_sessions_returns_empty_when_no_sessions() {
+        let result = select_sessions(
+            &[],
+            false,
+            SortOrder::Recent,
+            &Registry::default(),
+            24,
+            Action::Delete,
+        )
+        .unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn session_filters_offers_all_states_when_mixed() {
+        let filters = session_filters(&[session("live", false), session("dead", true)]);
+        assert_eq!(
+            filters,
+            vec![
+                SessionFilter::Exited,
+                SessionFilter::Running,
+                SessionFilter::All,
+            ]
+        );
+    }
+
+    #[test]
+    fn session_filters_offers_only_present_state_when_uniform() {
+        assert_eq!(
+            session_filters(&[session("a", true), session("b", true)]),
+            vec![SessionFilter::Exited]
+        );
+        assert_eq!(
+            session_filters(&[session("a", false)]),
+            vec![SessionFilter::Running]
+        );
+    }
+
+    #[test]
+    fn session_filter_matches_by_state() {
+        assert!(SessionFilter::Exited.matches(&session("dead", true)));
+        assert!(!SessionFilter::Exited.matches(&session("live", false)));
+        assert!(SessionFilter::Running.matches(&session("live", false)));
+        assert!(SessionFilter::All.matches(&session("dead", true)));
+    }
+
+    #[test]
+    fn session_labels_align_state_and_age_columns() {
+        let sessions = vec![
+            Session {
+                name: "api".to_string(),
+                age: Some(Duration::from_secs(120)),
+                exited: false,
+                current: false,
+            },
+            Session {
+                name: "dotfiles".to_string(),
+                age: Some(Duration::from_secs(2 * 86_400)),
+                exited: true,
+                current: false,
+            },
+        ];
+        assert_eq!(
+            session_labels(&sessions),
+            vec![
+                "api       running  created 2m ago",
+                "dotfiles  exited   created 2d ago"
+            ]
+        );
+    }
+
+    #[test]
+    fn sort_help_names_the_next_order() {
+        assert!(sort_help(SortOrder::Recent).ends_with("TAB to sort by created"));
+    }
+
+    #[test]
+    fn session_label_omits_unknown_age() {
+        let session = Session {
+            name: "api".to_string(),
+            age: None,
+            exited: false,
+            current: true,
+        };
+        assert_eq!(session_label(&session, 3), "api  current");
+    }
+
+    #[test]
+    fn validate_session_name_reports_collision_message() {
+        let existing = vec!["api".to_string()];
+        assert_eq!(
+            validate_session_name("api", &existing),
+            Validation::Invalid("Session already exists. Try 'api-2'.".into())
+        );
+        assert_eq!(validate_session_name("web", &existing), Validation::Valid);
+    }
+
+    #[test]
+    fn name_suggester_suggests_free_name_on_collision() {
+        let mut suggester = NameSuggester {
+            existing: vec!["api".to_string()],
+        };
+        assert_eq!(suggester.get_suggestions("api").unwrap(), vec!["api-2"]);
+        assert!(suggester.get_suggestions("web").unwrap().is_empty());
+        assert_eq!(
+            suggester.get_completion("api", None).unwrap(),
+            Some("api-2".to_string())
+        );
     }
 
     #[test]
@@ -276,4 +880,151 @@ mod tests {
         let items: Vec<String> = vec![];
         assert_eq!(longest_common_prefix(&items), "");
     }
+
+    #[test]
+    fn tilde_path_abbreviates_home() {
+        let home = Path::new("/home/me");
+        assert_eq!(tilde_path(Path::new("/home/me/src/api"), home), "~/src/api");
+        assert_eq!(tilde_path(home, home), "~");
+        assert_eq!(tilde_path(Path::new("/srv/app"), home), "/srv/app");
+    }
+
+    #[test]
+    fn directory_suggester_offers_ranked_dirs_first() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().to_path_buf();
+        std::fs::create_dir_all(home.join("api")).unwrap();
+        std::fs::create_dir_all(home.join("app")).unwrap();
+        let mut suggester = DirectorySuggester {
+            completer: DirCompleter::new(home.clone(), false),
+            ranked: vec!["/srv/apps/web".to_string(), "/srv/docs".to_string()],
+        };
+        assert_eq!(
+            suggester.get_suggestions("").unwrap(),
+            vec!["/srv/apps/web", "/srv/docs"]
+        );
+        assert_eq!(
+            suggester.get_suggestions("ap").unwrap(),
+            vec!["api", "app", "/srv/apps/web"]
+        );
+    }
+
+    fn completer_tree() -> tempfile::TempDir {
+        let tmp = tempfile::tempdir().unwrap();
+        for dir in ["src/api", "src/app", "work/web"] {
+            std::fs::create_dir_all(tmp.path().join(dir)).unwrap();
+        }
+        tmp
+    }
+
+    #[test]
+    fn dir_completer_completes_relative_and_parent_paths() {
+        let tmp = completer_tree();
+        let home = tmp.path().to_path_buf();
+        let mut completer = DirCompleter::new(home.join("work"), false);
+        assert_eq!(completer.get_suggestions("we").unwrap(), vec!["web"]);
+        assert_eq!(
+            completer.get_suggestions("../src/a").unwrap(),
+            vec!["../src/api", "../src/app"]
+        );
+    }
+
+    #[test]
+    fn dir_completer_shows_hidden_dirs_for_dot_prefix() {
+        let tmp = completer_tree();
+        let home = tmp.path().to_path_buf();
+        std::fs::create_dir_all(home.join(".config/nvim")).unwrap();
+        std::fs::create_dir_all(home.join(".local/share")).unwrap();
+        let mut completer = DirCompleter::new(home.clone(), false);
+        assert_eq!(completer.get_suggestions("").unwrap(), vec!["src", "work"]);
+        assert_eq!(completer.get_suggestions(".c").unwrap(), vec![".config"]);
+        assert_eq!(
+            completer.get_suggestions(".").unwrap(),
+            vec![".config", ".local"]
+        );
+        assert_eq!(
+            completer.get_suggestions(".local/").unwrap(),
+            vec![".local/share"]
+        );
+    }
+
+    #[test]
+    fn dir_completer_always_shows_hidden_dirs_when_enabled() {
+        let tmp = completer_tree();
+        let home = tmp.path().to_path_buf();
+        std::fs::create_dir_all(home.join(".dotfiles")).unwrap();
+        let mut completer = DirCompleter::new(home, true);
+        assert_eq!(
+            completer.get_suggestions("").unwrap(),
+            vec![".dotfiles", "src", "work"]
+        );
+    }
+
+    #[test]
+    fn dir_completer_completes_absolute_paths() {
+        let tmp = completer_tree();
+        let home = tmp.path().to_path_buf();
+        let mut completer = DirCompleter::new(PathBuf::from("/nonexistent"), false);
+        let input = format!("{}/src/ap", home.display());
+        assert_eq!(
+            completer.get_suggestions(&input).unwrap(),
+            vec![
+                format!("{}/src/api", home.display()),
+                format!("{}/src/app", home.display())
+            ]
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn dir_completer_expands_variables_but_keeps_them_in_suggestions() {
+        let tmp = completer_tree();
+        let home = tmp.path().to_path_buf();
+        unsafe { std::env::set_var("ZISM_TEST_COMPLETER", home.join("src")) };
+        let mut completer = DirCompleter::new(PathBuf::from("/nonexistent"), false);
+        assert_eq!(
+            completer
+                .get_suggestions("$ZISM_TEST_COMPLETER/api")
+                .unwrap(),
+            vec!["$ZISM_TEST_COMPLETER/api"]
+        );
+        assert_eq!(
+            completer.get_suggestions("$ZISM_TEST_COMPLETER").unwrap(),
+            vec!["$ZISM_TEST_COMPLETER"]
+        );
+        unsafe { std::env::remove_var("ZISM_TEST_COMPLETER") };
+    }
+
+    #[test]
+    fn validate_directory_accepts_existing_and_missing_directories() {
+        let tmp = completer_tree();
+        let home = tmp.path().to_path_buf();
+        std::fs::write(home.join("notes.txt"), "").unwrap();
+        assert_eq!(validate_directory("src/api", &home), Validation::Valid);
+        assert_eq!(
+            validate_directory(&format!("{}/src", home.display()), &home),
+            Validation::Valid
+        );
+        assert_eq!(validate_directory("src/missing", &home), Validation::Valid);
+        assert!(matches!(
+            validate_directory("notes.txt", &home),
+            Validation::Invalid(_)
+        ));
+        assert!(matches!(
+            validate_directory("  ", &home),
+            Validation::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn directory_suggester_without_ranked_dirs_completes_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().to_path_buf();
+        std::fs::create_dir_all(home.join("api")).unwrap();
+        let mut suggester = DirectorySuggester {
+            completer: DirCompleter::new(home, false),
+            ranked: Vec::new(),
+        };
+        assert_eq!(suggester.get_suggestions("").unwrap(), vec!["api"]);
+    }
 }
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+pub fn home_dir() -> PathBuf {
+    PathBuf::from(std::env::var("HOME").unwrap_or_default())
+}
+
+/// Resolves an XDG base directory, falling back to `$HOME/<fallback>` when
+/// the variable is unset or not absolute, as the spec requires.
+fn base_dir(var: &str, fallback: &str) -> PathBuf {
+    match std::env::var_os(var).map(PathBuf::from) {
+        Some(dir) if dir.is_absolute() => dir,
+        _ => home_dir().join(fallback),
+    }
+}
+
+pub fn config_home() -> PathBuf {
+    base_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn state_home() -> PathBuf {
+    base_dir("XDG_STATE_HOME", ".local/state")
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+    use crate::testutil::restore_var;
+
+    #[test]
+    #[serial]
+    fn config_home_uses_xdg_config_home_when_set() {
+        let previous = std::env::var_os("XDG_CONFIG_HOME");
+        unsafe { std::env::set_var("XDG_CONFIG_HOME", "/tmp/xdg-config") };
+        let dir = config_home();
+        restore_var("XDG_CONFIG_HOME", previous);
+        assert_eq!(dir, PathBuf::from("/tmp/xdg-config"));
+    }
+
+    #[test]
+    #[serial]
+    fn state_home_falls_back_to_local_state() {
+        let previous = std::env::var_os("XDG_STATE_HOME");
+        unsafe { std::env::remove_var("XDG_STATE_HOME") };
+        let dir = state_home();
+        restore_var("XDG_STATE_HOME", previous);
+        assert_eq!(dir, home_dir().join(".local/state"));
+    }
+
+    #[test]
+    #[serial]
+    fn config_home_ignores_relative_xdg_config_home() {
+        let previous = std::env::var_os("XDG_CONFIG_HOME");
+        unsafe { std::env::set_var("XDG_CONFIG_HOME", "relative") };
+        let dir = config_home();
+        restore_var("XDG_CONFIG_HOME", previous);
+        assert_eq!(dir, home_dir().join(".config"));
+    }
+}
@@ -1,35 +1,277 @@
+use std::collections::BTreeMap;
 use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 
 use anyhow::{Context, Result, bail};
 
-pub fn parse_sessions(output: &str) -> Vec<String> {
-    output
-        .lines()
-        .map(|line| line.trim().to_string())
-        .filter(|line| !line.is_empty())
-        .collect()
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub name: String,
+    /// Time elapsed since the session was created, as reported by Zellij.
+    pub age: Option<Duration>,
+    /// The session has exited and can be resurrected by attaching to it.
+    pub exited: bool,
+    /// The session zism is currently running inside of.
+    pub current: bool,
 }
 
-pub fn build_create_args(name: &str) -> Vec<String> {
-    vec!["--session".to_string(), name.to_string()]
+impl Session {
+    pub fn state(&self) -> &'static str {
+        if self.current {
+            "current"
+        } else if self.exited {
+            "exited"
+        } else {
+            "running"
+        }
+    }
+}
+
+/// Parses a humantime-style duration such as `1day 2h 3m 4s`.
+pub fn parse_age(input: &str) -> Option<Duration> {
+    let mut total = 0u64;
+    let mut parsed_any = false;
+    for token in input.split_whitespace() {
+        let split = token.find(|c: char| !c.is_ascii_digit())?;
+        let (value, unit) = token.split_at(split);
+        let value: u64 = value.parse().ok()?;
+        let secs = match unit {
+            "y" | "year" | "years" => 31_557_600,
+            "M" | "month" | "months" => 2_630_016,
+            "w" | "week" | "weeks" => 604_800,
+            "d" | "day" | "days" => 86_400,
+            "h" | "hour" | "hours" => 3_600,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "ms" | "us" | "ns" => 0,
+            _ => return None,
+        };
+        total += value * secs;
+        parsed_any = true;
+    }
+    parsed_any.then(|| Duration::from_secs(total))
+}
+
+/// Formats a duration using only its largest unit, e.g. `3d` or `15m`.
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3_600 => format!("{}m", secs / 60),
+        3_600..86_400 => format!("{}h", secs / 3_600),
+        _ => format!("{}d", secs / 86_400),
+    }
+}
+
+fn parse_session_line(line: &str) -> Option<Session> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    let Some(start) = line.find(" [Created ") else {
+        return Some(Session {
+            name: line.to_string(),
+            age: None,
+            exited: false,
+            current: false,
+        });
+    };
+
+    let name = line[..start].trim().to_string();
+    let rest = &line[start + " [Created ".len()..];
+    let (created, suffix) = rest.split_once(']').unwrap_or((rest, ""));
+    let age = parse_age(created.trim().trim_end_matches("ago"));
+
+    Some(Session {
+        name,
+        age,
+        exited: suffix.contains("EXITED"),
+        current: suffix.contains("(current)"),
+    })
+}
+
+/// Parses the output of `zellij list-sessions --no-formatting`.
+pub fn parse_sessions(output: &str) -> Vec<Session> {
+    output.lines().filter_map(parse_session_line).collect()
+}
+
+/// `sun_path` size of a Unix socket address, including the trailing NUL.
+#[cfg(target_os = "macos")]
+const SOCKET_PATH_MAX: usize = 104;
+#[cfg(not(target_os = "macos"))]
+const SOCKET_PATH_MAX: usize = 108;
+
+/// Subdirectory Zellij keeps its session sockets in.
+const SOCKET_CONTRACT_DIR: &str = "contract_version_1";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameError {
+    Empty,
+    Whitespace,
+    InvalidChar(char),
+    TooLong { max: usize },
+    Exists { suggestion: String },
+}
+
+impl std::fmt::Display for NameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NameError::Empty => write!(f, "Session name cannot be empty."),
+            NameError::Whitespace => write!(f, "Session name cannot contain whitespace."),
+            NameError::InvalidChar(c) => write!(f, "Session name cannot contain '{c}'."),
+            NameError::TooLong { max } => write!(
+                f,
+                "Session name must be at most {max} bytes to fit Zellij's socket path."
+            ),
+            NameError::Exists { suggestion } => {
+                write!(f, "Session already exists. Try '{suggestion}'.")
+            }
+        }
+    }
+}
+
+/// Directory Zellij creates session sockets in, resolved the same way Zellij
+/// does: `$ZELLIJ_SOCKET_DIR`, then `$XDG_RUNTIME_DIR/zellij`, then
+/// `/tmp/zellij-<uid>`.
+pub fn socket_dir() -> PathBuf {
+    let base = if let Some(dir) = std::env::var_os("ZELLIJ_SOCKET_DIR") {
+        PathBuf::from(dir)
+    } else if let Some(dir) = std::env::var_os("XDG_RUNTIME_DIR") {
+        PathBuf::from(dir).join("zellij")
+    } else {
+        let uid = unsafe { libc::getuid() };
+        PathBuf::from(format!("/tmp/zellij-{uid}"))
+    };
+    base.join(SOCKET_CONTRACT_DIR)
+}
+
+/// Longest session name whose socket path still fits in `sun_path`.
+pub fn max_session_name_len() -> usize {
+    let dir_len = socket_dir().as_os_str().len();
+    // One byte for the separator and one for the trailing NUL.
+    SOCKET_PATH_MAX.saturating_sub(dir_len + 2)
+}
+
+/// Returns `name-2`, `name-3`, ... whichever is the first not in `existing`.
+pub fn suggest_available_name(name: &str, existing: &[String]) -> String {
+    (2..)
+        .map(|n| format!("{name}-{n}"))
+        .find(|candidate| !existing.iter().any(|e| e == candidate))
+        .unwrap_or_default()
+}
+
+/// Checks `name` against the rules Zellij enforces plus collisions with
+/// `existing` session names.
+pub fn validate_session_name(name: &str, existing: &[String]) -> Result<(), NameError> {
+    if name.is_empty() || name.trim().is_empty() {
+        return Err(NameError::Empty);
+    }
+    if name.chars().any(char::is_whitespace) {
+        return Err(NameError::Whitespace);
+    }
+    if let Some(c) = name.chars().find(|c| *c == '/' || c.is_control()) {
+        return Err(NameError::InvalidChar(c));
+    }
+    let max = max_session_name_len();
+    if name.len() > max {
+        return Err(NameError::TooLong { max });
+    }
+    if existing.iter().any(|e| e == name) {
+        return Err(NameError::Exists {
+            suggestion: suggest_available_name(name, existing),
+        });
+    }
+    Ok(())
+}
+
+pub fn build_create_args(name: &str, layout: Option<&str>) -> Vec<String> {
+    let mut args = vec!["--session".to_string(), name.to_string()];
+    if let Some(layout) = layout {
+        args.push("--layout".to_string());
+        args.push(layout.to_string());
+    }
+    args
 }
 
 pub fn build_attach_args(name: &str) -> Vec<String> {
     vec!["attach".to_string(), name.to_string()]
 }
 
-pub fn build_delete_args(name: &str) -> Vec<String> {
+pub fn build_resurrect_args(name: &str, force_run_commands: bool) -> Vec<String> {
+    let mut args = build_attach_args(name);
+    if force_run_commands {
+        args.push("--force-run-commands".to_string());
+    }
+    args
+}
+
+/// Runs `rename-session` as an action against the target session, which
+/// works from outside Zellij.
+pub fn build_rename_args(name: &str, new_name: &str) -> Vec<String> {
     vec![
-        "delete-session".to_string(),
-        "-f".to_string(),
+        "--session".to_string(),
         name.to_string(),
+        "action".to_string(),
+        "rename-session".to_string(),
+        new_name.to_string(),
     ]
 }
 
-pub fn list_sessions() -> Result<Vec<String>> {
+/// Creates a session without attaching to it, so a client inside Zellij can
+/// switch to it afterwards.
+pub fn build_create_background_args(
+    name: &str,
+    cwd: Option<&Path>,
+    layout: Option<&str>,
+) -> Vec<String> {
+    let mut args = vec![
+        "attach".to_string(),
+        "--create-background".to_string(),
+        name.to_string(),
+    ];
+    if cwd.is_some() || layout.is_some() {
+        args.push("options".to_string());
+    }
+    if let Some(cwd) = cwd {
+        args.push("--default-cwd".to_string());
+        args.push(cwd.display().to_string());
+    }
+    if let Some(layout) = layout {
+        args.push("--default-layout".to_string());
+        args.push(layout.to_string());
+    }
+    args
+}
+
+/// Moves the current client to another session, resurrecting it if it exited.
+pub fn build_switch_args(name: &str) -> Vec<String> {
+    vec![
+        "action".to_string(),
+        "switch-session".to_string(),
+        name.to_string(),
+    ]
+}
+
+pub fn build_kill_args(name: &str) -> Vec<String> {
+    vec!["kill-session".to_string(), name.to_string()]
+}
+
+/// Without `force`, Zellij refuses to delete sessions that are still running.
+pub fn build_delete_args(name: &str, force: bool) -> Vec<String> {
+    let mut args = vec!["delete-session".to_string()];
+    if force {
+        args.push("-f".to_string());
+    }
+    args.push(name.to_string());
+    args
+}
+
+pub fn list_sessions() -> Result<Vec<Session>> {
     let output = Command::new("zellij")
-        .args(["list-sessions", "--short", "--no-formatting"])
+        .args(["list-sessions", "--no-formatting"])
         .output()
         .context("Failed to run zellij. Is it installed?")?;
 
@@ -49,15 +291,28 @@ pub fn list_sessions() -> Result<Vec<String>> {
 /// current process with Zellij on success. This means the function never
 /// returns `Ok(())` — it only returns `Err` if the exec fails. Callers
 /// should treat the `Result<()>` as representing only the error path.
-pub fn create_session(name: &str) -> Result<()> {
-    let err = Command::new("zellij").args(build_create_args(name)).exec();
+pub fn create_session(
+    name: &str,
+    layout: Option<&str>,
+    env: &BTreeMap<String, String>,
+) -> Result<()> {
+    let err = Command::new("zellij")
+        .envs(env)
+        .args(build_create_args(name, layout))
+        .exec();
     bail!("Failed to exec zellij: {err}");
 }
 
-pub fn create_session_with_dir(name: &str, cwd: &std::path::Path) -> Result<()> {
+pub fn create_session_with_dir(
+    name: &str,
+    cwd: &Path,
+    layout: Option<&str>,
+    env: &BTreeMap<String, String>,
+) -> Result<()> {
     let err = Command::new("zellij")
         .current_dir(cwd)
-        .args(build_create_args(name))
+        .envs(env)
+        .args(build_create_args(name, layout))
         .exec();
     bail!("Failed to exec zellij: {err}");
 }
@@ -72,9 +327,91 @@ pub fn attach_session(name: &str) -> Result<()> {
     bail!("Failed to exec zellij: {err}");
 }
 
-pub fn delete_session(name: &str) -> Result<()> {
+/// Resurrects an exited Zellij session via `Command::exec()`. Like
+/// `attach_session`, this only returns on failure.
+pub fn resurrect_session(name: &str, force_run_commands: bool) -> Result<()> {
+    let err = Command::new("zellij")
+        .args(build_resurrect_args(name, force_run_commands))
+        .exec();
+    bail!("Failed to exec zellij: {err}");
+}
+
+/// Starts a session in the background. The variables Zellij sets for the
+/// current session are dropped so the new one does not inherit them.
+pub fn create_session_in_background(
+    name: &str,
+    cwd: Option<&Path>,
+    layout: Option<&str>,
+    env: &BTreeMap<String, String>,
+) -> Result<()> {
+    let mut command = Command::new("zellij");
+    command
+        .env_remove("ZELLIJ")
+        .env_remove("ZELLIJ_SESSION_NAME")
+        .env_remove("ZELLIJ_PANE_ID")
+        .envs(env)
+        .args(build_create_background_args(name, cwd, layout));
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    let status = command
+        .status()
+        .context("Failed to run zellij attach --create-background")?;
+
+    if !status.success() {
+        bail!("Failed to create session '{name}'");
+    }
+
+    Ok(())
+}
+
+/// Switches the client zism runs in to `name`. Only works inside Zellij.
+pub fn switch_session(name: &str) -> Result<()> {
+    let status = Command::new("zellij")
+        .args(build_switch_args(name))
+        .status()
+        .context("Failed to run zellij action switch-session")?;
+
+    if !status.success() {
+        bail!("Failed to switch to session '{name}'");
+    }
+
+    Ok(())
+}
+
+pub fn rename_session(name: &str, new_name: &str) -> Result<()> {
     let status = Command::new("zellij")
-        .args(build_delete_args(name))
+        .args(build_rename_args(name, new_name))
+        .status()
+        .context("Failed to run zellij action rename-session")?;
+
+    if !status.success() {
+        bail!("Failed to rename session '{name}' to '{new_name}'");
+    }
+
+    Ok(())
+}
+
+/// Stops a running session. Its resurrection data is kept, so it shows up as
+/// exited afterwards.
+pub fn kill_session(name: &str) -> Result<()> {
+    let status = Command::new("zellij")
+        .args(build_kill_args(name))
+        .status()
+        .context("Failed to run zellij kill-session")?;
+
+    if !status.success() {
+        bail!("Failed to kill session '{name}'");
+    }
+
+    Ok(())
+}
+
+/// Deletes a session and its resurrection data. `force` kills it first if it
+/// is still running.
+pub fn delete_session(name: &str, force: bool) -> Result<()> {
+    let status = Command::new("zellij")
+        .args(build_delete_args(name, force))
         .status()
         .context("Failed to run zellij delete-session")?;
 
@@ -87,27 +424,37 @@ pub fn delete_session(name: &str) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
+    use serial_test::serial;
+
     use super::*;
+    use crate::testutil::restore_var;
+
+    fn names(sessions: &[Session]) -> Vec<&str> {
+        sessions.iter().map(|s| s.name.as_str()).collect()
+    }
 
     #[test]
     fn parse_sessions_returns_session_names() {
         let output = "my-project\ndev-server\ndotfiles\n";
         let sessions = parse_sessions(output);
-        assert_eq!(sessions, vec!["my-project", "dev-server", "dotfiles"]);
+        assert_eq!(
+            names(&sessions),
+            vec!["my-project", "dev-server", "dotfiles"]
+        );
     }
 
     #[test]
     fn parse_sessions_trims_whitespace() {
         let output = "  my-project  \n  dev-server  \n";
         let sessions = parse_sessions(output);
-        assert_eq!(sessions, vec!["my-project", "dev-server"]);
+        assert_eq!(names(&sessions), vec!["my-project", "dev-server"]);
     }
 
     #[test]
     fn parse_sessions_skips_empty_lines() {
         let output = "my-project\n\n\ndev-server\n";
         let sessions = parse_sessions(output);
-        assert_eq!(sessions, vec!["my-project", "dev-server"]);
+        assert_eq!(names(&sessions), vec!["my-project", "dev-server"]);
     }
 
     #[test]
@@ -116,21 +463,227 @@ mod tests {
         assert!(sessions.is_empty());
     }
 
+    #[test]
+    fn parse_sessions_parses_running_session() {
+        let sessions = parse_sessions("my-project [Created 2h 3m 4s ago] \n");
+        assert_eq!(
+            sessions,
+            vec![Session {
+                name: "my-project".to_string(),
+                age: Some(Duration::from_secs(2 * 3_600 + 3 * 60 + 4)),
+                exited: false,
+                current: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_sessions_parses_current_session() {
+        let sessions = parse_sessions("dotfiles [Created 10s ago] (current)\n");
+        assert!(sessions[0].current);
+        assert!(!sessions[0].exited);
+        assert_eq!(sessions[0].state(), "current");
+    }
+
+    #[test]
+    fn parse_sessions_parses_exited_session() {
+        let sessions =
+            parse_sessions("old-work [Created 1day 4h ago] (EXITED - attach to resurrect)\n");
+        assert_eq!(sessions[0].name, "old-work");
+        assert!(sessions[0].exited);
+        assert_eq!(
+            sessions[0].age,
+            Some(Duration::from_secs(86_400 + 4 * 3_600))
+        );
+        assert_eq!(sessions[0].state(), "exited");
+    }
+
+    #[test]
+    fn parse_age_returns_none_for_unknown_unit() {
+        assert_eq!(parse_age("3 parsecs"), None);
+        assert_eq!(parse_age(""), None);
+    }
+
+    #[test]
+    fn format_age_uses_largest_unit() {
+        assert_eq!(format_age(Duration::from_secs(42)), "42s");
+        assert_eq!(format_age(Duration::from_secs(5 * 60 + 3)), "5m");
+        assert_eq!(format_age(Duration::from_secs(3 * 3_600 + 59)), "3h");
+        assert_eq!(format_age(Duration::from_secs(2 * 86_400 + 1)), "2d");
+    }
+
+    fn existing(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn validate_session_name_accepts_valid_name() {
+        assert_eq!(
+            validate_session_name("api-dev", &existing(&["web"])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn validate_session_name_rejects_empty() {
+        assert_eq!(validate_session_name("", &[]), Err(NameError::Empty));
+        assert_eq!(validate_session_name("  ", &[]), Err(NameError::Empty));
+    }
+
+    #[test]
+    fn validate_session_name_rejects_whitespace() {
+        assert_eq!(
+            validate_session_name("my project", &[]),
+            Err(NameError::Whitespace)
+        );
+    }
+
+    #[test]
+    fn validate_session_name_rejects_slash() {
+        assert_eq!(
+            validate_session_name("a/b", &[]),
+            Err(NameError::InvalidChar('/'))
+        );
+    }
+
+    #[test]
+    fn validate_session_name_rejects_collision_with_suggestion() {
+        assert_eq!(
+            validate_session_name("api", &existing(&["api", "api-2"])),
+            Err(NameError::Exists {
+                suggestion: "api-3".to_string()
+            })
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn validate_session_name_rejects_names_longer_than_socket_path_allows() {
+        let previous = std::env::var_os("ZELLIJ_SOCKET_DIR");
+        unsafe { std::env::set_var("ZELLIJ_SOCKET_DIR", "/tmp/zism-test") };
+        let max = SOCKET_PATH_MAX - "/tmp/zism-test/contract_version_1".len() - 2;
+        assert_eq!(max_session_name_len(), max);
+        assert_eq!(validate_session_name(&"a".repeat(max), &[]), Ok(()));
+        assert_eq!(
+            validate_session_name(&"a".repeat(max + 1), &[]),
+            Err(NameError::TooLong { max })
+        );
+        restore_var("ZELLIJ_SOCKET_DIR", previous);
+    }
+
+    #[test]
+    #[serial]
+    fn socket_dir_prefers_xdg_runtime_dir() {
+        let socket_dir_var = std::env::var_os("ZELLIJ_SOCKET_DIR");
+        let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR");
+        unsafe { std::env::remove_var("ZELLIJ_SOCKET_DIR") };
+        unsafe { std::env::set_var("XDG_RUNTIME_DIR", "/run/user/1000") };
+        let dir = socket_dir();
+        restore_var("ZELLIJ_SOCKET_DIR", socket_dir_var);
+        restore_var("XDG_RUNTIME_DIR", runtime_dir);
+        assert_eq!(
+            dir,
+            PathBuf::from("/run/user/1000/zellij/contract_version_1")
+        );
+    }
+
+    #[test]
+    fn suggest_available_name_skips_taken_suffixes() {
+        assert_eq!(suggest_available_name("api", &existing(&["api"])), "api-2");
+        assert_eq!(
+            suggest_available_name("api", &existing(&["api", "api-2", "api-3"])),
+            "api-4"
+        );
+    }
+
     #[test]
     fn build_create_args_returns_correct_args() {
-        let args = build_create_args("my-session");
+        let args = build_create_args("my-session", None);
         assert_eq!(args, vec!["--session", "my-session"]);
     }
 
+    #[test]
+    fn build_create_args_with_layout_returns_correct_args() {
+        let args = build_create_args("my-session", Some("compact"));
+        assert_eq!(args, vec!["--session", "my-session", "--layout", "compact"]);
+    }
+
+    #[test]
+    fn build_create_background_args_returns_correct_args() {
+        let args = build_create_background_args("my-session", None, None);
+        assert_eq!(args, vec!["attach", "--create-background", "my-session"]);
+    }
+
+    #[test]
+    fn build_create_background_args_passes_cwd_and_layout_as_options() {
+        let args = build_create_background_args(
+            "my-session",
+            Some(Path::new("/srv/app")),
+            Some("compact"),
+        );
+        assert_eq!(
+            args,
+            vec![
+                "attach",
+                "--create-background",
+                "my-session",
+                "options",
+                "--default-cwd",
+                "/srv/app",
+                "--default-layout",
+                "compact"
+            ]
+        );
+    }
+
+    #[test]
+    fn build_switch_args_returns_correct_args() {
+        let args = build_switch_args("my-session");
+        assert_eq!(args, vec!["action", "switch-session", "my-session"]);
+    }
+
     #[test]
     fn build_attach_args_returns_correct_args() {
         let args = build_attach_args("my-session");
         assert_eq!(args, vec!["attach", "my-session"]);
     }
 
+    #[test]
+    fn build_resurrect_args_returns_attach_args() {
+        let args = build_resurrect_args("my-session", false);
+        assert_eq!(args, vec!["attach", "my-session"]);
+    }
+
+    #[test]
+    fn build_resurrect_args_appends_force_run_commands() {
+        let args = build_resurrect_args("my-session", true);
+        assert_eq!(args, vec!["attach", "my-session", "--force-run-commands"]);
+    }
+
+    #[test]
+    fn build_rename_args_returns_correct_args() {
+        let args = build_rename_args("old", "new");
+        assert_eq!(
+            args,
+            vec!["--session", "old", "action", "rename-session", "new"]
+        );
+    }
+
+    #[test]
+    fn build_kill_args_returns_correct_args() {
+        let args = build_kill_args("my-session");
+        assert_eq!(args, vec!["kill-session", "my-session"]);
+    }
+
     #[test]
     fn build_delete_args_returns_correct_args() {
-        let args = build_delete_args("my-session");
+        let args = build_delete_args("my-session", false);
+        assert_eq!(args, vec!["delete-session", "my-session"]);
+    }
+
+    #[test]
+    fn build_delete_args_with_force_returns_correct_args() {
+        let args = build_delete_args("my-session", true);
         assert_eq!(args, vec!["delete-session", "-f", "my-session"]);
     }
 }
@@ -0,0 +1,49 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Paths from `zoxide query -l -s` output, which prints `<score> <path>`
+/// lines best first.
+pub fn parse_query(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .filter_map(|line| line.trim_start().split_once(' '))
+        .map(|(_, path)| PathBuf::from(path.trim_start()))
+        .collect()
+}
+
+/// Existing directories in zoxide's database, best first. Empty when zoxide
+/// is not installed or fails.
+pub fn ranked_dirs() -> Vec<PathBuf> {
+    let Ok(output) = Command::new("zoxide").args(["query", "-l", "-s"]).output() else {
+        return Vec::new();
+    };
+    if !output.status.success() {
+        return Vec::new();
+    }
+    parse_query(&String::from_utf8_lossy(&output.stdout))
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_query_keeps_zoxide_order() {
+        let output = "  42.0 /home/me/src/api\n   8.5 /home/me/my notes\n";
+        assert_eq!(
+            parse_query(output),
+            vec![
+                PathBuf::from("/home/me/src/api"),
+                PathBuf::from("/home/me/my notes")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_query_skips_malformed_lines() {
+        assert_eq!(parse_query("\n42.0\n"), Vec::<PathBuf>::new());
+    }
+}